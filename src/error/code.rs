@@ -0,0 +1,59 @@
+// Stable identifiers for every diagnostic. Codes are never reused: when a
+// check goes away its code is retired with it.
+
+// Lexer
+pub const UNEXPECTED_CHARACTER: &str = "E0001";
+pub const UNTERMINATED_STRING: &str = "E0002";
+pub const INVALID_NUMBER: &str = "E0003";
+
+// Parser
+pub const EXPECTED_EXPRESSION: &str = "E0100";
+pub const EXPECTED_TOKEN: &str = "E0101";
+pub const INVALID_ASSIGNMENT_TARGET: &str = "E0102";
+pub const TOO_MANY_ARGUMENTS: &str = "E0103";
+pub const UNCLOSED_BLOCK: &str = "E0104";
+
+// Resolver
+pub const SELF_INITIALIZER: &str = "E0200";
+pub const DUPLICATE_VARIABLE: &str = "E0201";
+pub const TOP_LEVEL_RETURN: &str = "E0202";
+pub const INITIALIZER_RETURN: &str = "E0203";
+pub const SELF_INHERITANCE: &str = "E0204";
+pub const DUPLICATE_FIELD: &str = "E0205";
+pub const RECORD_INITIALIZER: &str = "E0206";
+pub const THIS_OUTSIDE_CLASS: &str = "E0207";
+pub const SUPER_OUTSIDE_CLASS: &str = "E0208";
+pub const SUPER_WITHOUT_SUPERCLASS: &str = "E0209";
+
+// Interpreter. E0300 was used for every runtime error before they had kinds.
+pub const TYPE_ERROR: &str = "E0301";
+pub const NAME_ERROR: &str = "E0302";
+pub const ARITY_ERROR: &str = "E0303";
+pub const PROPERTY_ERROR: &str = "E0304";
+pub const ZERO_DIVISION: &str = "E0305";
+pub const INDEX_ERROR: &str = "E0306";
+pub const VALUE_ERROR: &str = "E0307";
+pub const IO_ERROR: &str = "E0308";
+pub const USER_THROWN: &str = "E0309";
+pub const EXIT: &str = "E0310";
+pub const SYNTAX_ERROR: &str = "E0311";
+
+// Warnings
+pub const UNUSED_VARIABLE: &str = "W0001";
+pub const UNUSED_PARAMETER: &str = "W0002";
+pub const SHADOWED_VARIABLE: &str = "W0003";
+pub const UNREACHABLE_CODE: &str = "W0004";
+pub const SELF_ASSIGNMENT: &str = "W0005";
+pub const CONSTANT_CONDITION: &str = "W0006";
+
+// The name that allows a warning in a `// @allow(name)` comment.
+pub fn warning_name(code: &str) -> Option<&'static str> {
+    match code {
+        UNUSED_VARIABLE | UNUSED_PARAMETER => Some("unused"),
+        SHADOWED_VARIABLE => Some("shadow"),
+        UNREACHABLE_CODE => Some("unreachable"),
+        SELF_ASSIGNMENT => Some("self-assign"),
+        CONSTANT_CONDITION => Some("constant-condition"),
+        _ => None,
+    }
+}
@@ -0,0 +1,296 @@
+use super::code;
+use crate::lexer::token::{Span, Token, TokenType};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+// The stage of the pipeline that found the problem.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Lex,
+    Parse,
+    Resolve,
+    Runtime,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Lex => "lex",
+            Phase::Parse => "parse",
+            Phase::Resolve => "resolve",
+            Phase::Runtime => "runtime",
+        }
+    }
+}
+
+// A problem tied to a span of the source. Displays as the one-line
+// "[line N] Error at 'x': message" form; `render` adds the source snippet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub phase: Phase,
+    pub code: &'static str,
+    pub span: Span,
+    line: u32,
+    location: String,
+    message: String,
+    notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(
+        severity: Severity,
+        phase: Phase,
+        code: &'static str,
+        span: Span,
+        location: &str,
+        message: &str,
+    ) -> Diagnostic {
+        Diagnostic {
+            severity,
+            phase,
+            code,
+            span,
+            line: span.line,
+            location: location.to_owned(),
+            message: message.to_owned(),
+            notes: Vec::new(),
+        }
+    }
+
+    // An error pointing at `token`.
+    pub fn error(phase: Phase, code: &'static str, token: &Token, message: &str) -> Diagnostic {
+        Diagnostic::new(
+            Severity::Error,
+            phase,
+            code,
+            token.span(),
+            &location(token),
+            message,
+        )
+    }
+
+    pub fn warning(phase: Phase, code: &'static str, span: Span, message: &str) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, phase, code, span, "", message)
+    }
+
+    // Underlines `span` in the snippet but keeps the line in the header.
+    pub fn spanning(self, span: Span) -> Diagnostic {
+        Diagnostic { span, ..self }
+    }
+
+    // Turns a warning into an error, for --warnings-as-errors.
+    pub fn deny(self) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            ..self
+        }
+    }
+
+    // A line printed after the snippet.
+    pub fn note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_owned());
+        self
+    }
+
+    // One line of JSON for tools. Line and column are where the span
+    // starts; the offset and length of the span are in bytes.
+    pub fn to_json(&self, file: &str) -> String {
+        let span = format!(
+            "{{\"offset\":{},\"length\":{}}}",
+            self.span.offset, self.span.length
+        );
+        let notes: Vec<String> = self.notes.iter().map(|note| quote(note)).collect();
+        let fields = [
+            ("file", quote(file)),
+            ("line", self.span.line.to_string()),
+            ("column", self.span.column.to_string()),
+            ("span", span),
+            ("severity", quote(self.severity.name())),
+            ("phase", quote(self.phase.name())),
+            ("code", quote(self.code)),
+            ("message", quote(&self.message)),
+            ("notes", format!("[{}]", notes.join(","))),
+        ];
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("\"{}\":{}", key, value))
+            .collect();
+        format!("{{{}}}", fields.join(","))
+    }
+
+    pub fn render(&self, source: &str) -> String {
+        let mut out = self.to_string();
+        if let Some(snippet) = snippet(source, &self.span) {
+            out.push('\n');
+            out.push_str(&snippet);
+        }
+        for note in &self.notes {
+            out.push('\n');
+            out.push_str(note);
+        }
+        out
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+        };
+        write!(f, "[line {}] {}", self.line, severity)?;
+        if !self.location.is_empty() {
+            write!(f, " {}", self.location)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+// A `// @allow(name, ...)` comment. It silences the named warnings on its
+// own line and on the line after it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Allow {
+    pub line: u32,
+    pub names: Vec<String>,
+}
+
+impl Allow {
+    // Reads the annotation out of the text of a line comment.
+    pub fn parse(line: u32, comment: &str) -> Option<Allow> {
+        let names = comment
+            .trim()
+            .strip_prefix("@allow(")?
+            .strip_suffix(')')?
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .collect();
+        Some(Allow { line, names })
+    }
+
+    pub fn permits(&self, diagnostic: &Diagnostic) -> bool {
+        let name = match code::warning_name(diagnostic.code) {
+            Some(name) => name,
+            None => return false,
+        };
+        (diagnostic.line == self.line || diagnostic.line == self.line + 1)
+            && self.names.iter().any(|allowed| allowed == name)
+    }
+}
+
+pub fn location(token: &Token) -> String {
+    match token.typ {
+        TokenType::Eof => "at end".to_string(),
+        _ => format!("at '{}'", token.lexeme),
+    }
+}
+
+// `s` as a JSON string literal.
+pub fn quote(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// The source lines a span covers, each underlined with carets where the
+// span touches it. Spans over more than two lines show only the first and
+// the last. Returns None when the span does not fit the source, for example
+// when it belongs to an earlier line typed at the prompt.
+pub fn snippet(source: &str, span: &Span) -> Option<String> {
+    let end = span.offset + span.length;
+    if source.is_empty()
+        || end > source.len()
+        || !source.is_char_boundary(span.offset)
+        || !source.is_char_boundary(end)
+    {
+        return None;
+    }
+    let mut lines = Vec::new();
+    let mut start = source[..span.offset].rfind('\n').map_or(0, |i| i + 1);
+    let mut number = span.line;
+    loop {
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        lines.push((number, start, line_end));
+        if line_end >= end || line_end == source.len() {
+            break;
+        }
+        start = line_end + 1;
+        number += 1;
+    }
+    if lines.len() > 2 {
+        lines.drain(1..lines.len() - 1);
+        lines.insert(1, (0, 0, 0));
+    }
+    let width = number.to_string().len();
+    let gutter = " ".repeat(width);
+    let mut out = format!("{} |", gutter);
+    for (number, start, line_end) in lines {
+        if number == 0 {
+            out.push_str(&format!("\n{} | ...", gutter));
+            continue;
+        }
+        let text = source[start..line_end].trim_end_matches('\r');
+        out.push_str(&format!("\n{:>width$} | {}", number, text, width = width));
+        let from = span.offset.max(start);
+        let to = end.min(start + text.len()).max(from);
+        let indent: String = source[start..from]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(source[from..to].chars().count().max(1));
+        out.push_str(&format!("\n{} | {}{}", gutter, indent, carets));
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_includes_notes() {
+        let span = Span::new(4, 2, 1, 5);
+        let diagnostic =
+            Diagnostic::warning(Phase::Resolve, code::UNUSED_VARIABLE, span, "Unused.")
+                .note("a \"quoted\" note")
+                .note("tab\there");
+        let json = diagnostic.to_json("x.lox");
+        assert!(json.ends_with(r#""notes":["a \"quoted\" note","tab\there"]}"#));
+        let bare = Diagnostic::warning(Phase::Parse, code::UNREACHABLE_CODE, span, "Unreachable.");
+        assert!(bare.to_json("x.lox").ends_with(r#""notes":[]}"#));
+    }
+
+    #[test]
+    fn quotes_control_characters() {
+        assert_eq!(quote("a\"b\\c\n\u{1}"), r#""a\"b\\c\n\u0001""#);
+    }
+}
@@ -0,0 +1,34 @@
+use super::diagnostic::Diagnostic;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+// Prints diagnostics to stderr in the format picked on the command line.
+#[derive(Debug)]
+pub struct Emitter {
+    pub format: ErrorFormat,
+    // The script the diagnostics are about, for the JSON output.
+    pub file: String,
+    // Report warnings as errors and fail on them.
+    pub deny_warnings: bool,
+}
+
+impl Emitter {
+    pub fn new(format: ErrorFormat, file: &str) -> Emitter {
+        Emitter {
+            format,
+            file: file.to_owned(),
+            deny_warnings: false,
+        }
+    }
+
+    pub fn emit(&self, diagnostic: &Diagnostic, source: &str) {
+        match self.format {
+            ErrorFormat::Human => eprintln!("{}", diagnostic.render(source)),
+            ErrorFormat::Json => eprintln!("{}", diagnostic.to_json(&self.file)),
+        }
+    }
+}
@@ -1 +1,5 @@
+pub mod code;
+pub mod diagnostic;
+pub mod emitter;
 pub mod report;
+pub mod suggest;
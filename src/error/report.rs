@@ -1,30 +1,187 @@
-use crate::lexer::token::{Token, TokenType};
+use super::code;
+use super::diagnostic::{location, Diagnostic, Phase, Severity};
+use crate::interpreter::interpreter::Frame;
+use crate::lexer::token::{Span, Token};
+use std::error::Error;
+use std::fmt;
 
+// What went wrong, for code that handles runtime errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    // An operand or argument of the wrong type, or calling a non-callable.
+    TypeError,
+    // An undefined variable.
+    NameError,
+    // A call with the wrong number of arguments.
+    ArityError,
+    // A missing property, or a property access on a non-instance.
+    PropertyError,
+    ZeroDivision,
+    // An index outside of a list or string.
+    IndexError,
+    // An argument of the right type but an unusable value.
+    ValueError,
+    // A failed file or stream operation, or one that is not allowed.
+    IoError,
+    // An error raised with throw().
+    UserThrown,
+    // A call to exit(), which unwinds like an error.
+    Exit,
+    // Code that failed to parse, run by a caller that ignored the errors.
+    SyntaxError,
+}
+
+impl ErrorKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::TypeError => "TypeError",
+            ErrorKind::NameError => "NameError",
+            ErrorKind::ArityError => "ArityError",
+            ErrorKind::PropertyError => "PropertyError",
+            ErrorKind::ZeroDivision => "ZeroDivision",
+            ErrorKind::IndexError => "IndexError",
+            ErrorKind::ValueError => "ValueError",
+            ErrorKind::IoError => "IoError",
+            ErrorKind::UserThrown => "UserThrown",
+            ErrorKind::Exit => "Exit",
+            ErrorKind::SyntaxError => "SyntaxError",
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::TypeError => code::TYPE_ERROR,
+            ErrorKind::NameError => code::NAME_ERROR,
+            ErrorKind::ArityError => code::ARITY_ERROR,
+            ErrorKind::PropertyError => code::PROPERTY_ERROR,
+            ErrorKind::ZeroDivision => code::ZERO_DIVISION,
+            ErrorKind::IndexError => code::INDEX_ERROR,
+            ErrorKind::ValueError => code::VALUE_ERROR,
+            ErrorKind::IoError => code::IO_ERROR,
+            ErrorKind::UserThrown => code::USER_THROWN,
+            ErrorKind::Exit => code::EXIT,
+            ErrorKind::SyntaxError => code::SYNTAX_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct RuntimeError {
+    kind: ErrorKind,
     token: Token,
     message: String,
+    exit_code: Option<i32>,
+    stack: Option<Vec<Frame>>,
 }
 
 impl RuntimeError {
-    pub fn new(token: Token, message: &str) -> RuntimeError {
+    pub fn new(kind: ErrorKind, token: Token, message: &str) -> RuntimeError {
         RuntimeError {
-            token: token,
+            kind,
+            token,
             message: message.to_owned(),
+            exit_code: None,
+            stack: None,
         }
     }
-}
 
-pub fn runtime_report(err: RuntimeError) -> String {
-    error(&err.token, &err.message)
-}
+    // Not an error as such: unwinds the interpreter on a call to exit().
+    pub fn exit(token: Token, code: i32) -> RuntimeError {
+        RuntimeError {
+            kind: ErrorKind::Exit,
+            token,
+            message: format!("Exit with status {}.", code),
+            exit_code: Some(code),
+            stack: None,
+        }
+    }
 
-pub fn error(token: &Token, message: &str) -> String {
-    match token.typ {
-        TokenType::Eof => report(token.line, "at end", message),
-        _ => report(token.line, &format!("at '{}'", token.lexeme), message),
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    // Where in the source the error happened.
+    pub fn span(&self) -> Span {
+        self.token.span()
+    }
+
+    // The calls that were active when the error happened, outermost first.
+    // Empty for an error in top-level code.
+    pub fn stack(&self) -> &[Frame] {
+        self.stack.as_deref().unwrap_or_default()
+    }
+
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn line(&self) -> u32 {
+        self.token.line
+    }
+
+    // Records the call stack at the point of failure. The innermost call
+    // sees the error first, so later calls on the way out leave it alone.
+    pub fn with_stack(mut self, stack: &[Frame]) -> RuntimeError {
+        if self.stack.is_none() {
+            self.stack = Some(stack.to_vec());
+        }
+        self
+    }
+
+    // Every call that was active, innermost first, with the line each had
+    // reached. The script itself comes last.
+    pub fn traceback(&self) -> Vec<(String, u32)> {
+        let mut line = self.token.line;
+        let mut frames = Vec::new();
+        for frame in self.stack().iter().rev() {
+            frames.push((frame.function.clone(), line));
+            line = frame.line;
+        }
+        frames.push(("script".to_string(), line));
+        frames
+    }
+
+    // The error as printed when it reaches the top level, followed by
+    // the traceback.
+    pub fn diagnostic(&self) -> Diagnostic {
+        let mut diagnostic = Diagnostic::new(
+            Severity::Error,
+            Phase::Runtime,
+            self.kind.code(),
+            self.span(),
+            &location(&self.token),
+            &self.message,
+        );
+        if self.stack().is_empty() {
+            return diagnostic;
+        }
+        diagnostic = diagnostic.note("Traceback (most recent call first):");
+        for (function, line) in self.traceback() {
+            let caller = match function.as_str() {
+                "script" => function,
+                _ => format!("{}()", function),
+            };
+            diagnostic = diagnostic.note(&format!("  [line {}] in {}", line, caller));
+        }
+        diagnostic
     }
 }
 
-fn report(line: u32, offender: &str, message: &str) -> String {
-    format!("[line {}] Error {}: {}", line, offender, message)
+// The one-line form of the error, as printed before the snippet.
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.diagnostic())
+    }
 }
+
+impl Error for RuntimeError {}
@@ -1,71 +1,111 @@
-use crate::error::report::RuntimeError;
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::error::suggest;
 use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
 use crate::interpreter::object::Object;
-use crate::lexer::literal::{Instance, Literal};
+use crate::lexer::literal::{next_id, Instance, Literal};
 use crate::lexer::token::Token;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug)]
 pub struct Class {
+    pub id: usize,
     pub name: String,
     pub methods: HashMap<String, Literal>,
+    pub statics: HashMap<String, Literal>,
     pub super_class: Option<Rc<RefCell<Class>>>,
+    pub fields: Option<Vec<String>>,
 }
 
 impl Class {
     pub fn new(
         name: String,
         methods: HashMap<String, Literal>,
+        statics: HashMap<String, Literal>,
         super_class: Option<Rc<RefCell<Class>>>,
     ) -> Class {
         Class {
+            id: next_id(),
             name,
             methods,
+            statics,
             super_class,
+            fields: None,
         }
     }
 
-    #[allow(dead_code)]
+    pub fn record(
+        name: String,
+        fields: Vec<String>,
+        methods: HashMap<String, Literal>,
+        statics: HashMap<String, Literal>,
+    ) -> Class {
+        Class {
+            id: next_id(),
+            name,
+            methods,
+            statics,
+            super_class: None,
+            fields: Some(fields),
+        }
+    }
+
+    pub fn is_record(&self) -> bool {
+        self.fields.is_some()
+    }
+
     pub fn arity(&self) -> usize {
+        if let Some(fields) = &self.fields {
+            return fields.len();
+        }
         if let Some(Literal::Fun(init)) = self.find_method(&"init".to_string()) {
             return init.arity;
         }
-        return 0;
+        0
     }
 
     pub fn call(
-        self,
+        class: &Rc<RefCell<Class>>,
         interpreter: &mut Interpreter,
+        call_site: &Token,
         args: &Vec<Literal>,
     ) -> RuntimeResult<Literal> {
-        let instance = Object::new(self);
-        let init_function = instance.class.borrow().find_method(&"init".to_string());
+        let mut instance = Object::new(Rc::clone(class));
+        if let Some(fields) = &class.borrow().fields {
+            for (field, arg) in fields.iter().zip(args) {
+                instance.fields.insert(field.clone(), arg.clone());
+            }
+        }
+        let init_function = class.borrow().find_method(&"init".to_string());
         let wrapped_instance = Rc::new(RefCell::new(instance));
         if let Some(Literal::Fun(init)) = init_function {
             if let Literal::Fun(bound_init) =
                 init.bind(Instance::Dynamic(Rc::clone(&wrapped_instance)), false)
             {
-                bound_init.call(interpreter, args)?;
+                let name = class.borrow().name.clone();
+                bound_init.call_as(interpreter, call_site, args, name)?;
             }
         }
-        return Ok(Literal::Instance(Instance::Dynamic(wrapped_instance)));
+        Ok(Literal::Instance(Instance::Dynamic(wrapped_instance)))
     }
 
-    pub fn get(&self, name: &Token) -> RuntimeResult<Literal> {
-        if let Some(Literal::Fun(method)) = self.find_method(&name.lexeme) {
-            return Ok(method.bind(Instance::Static(Rc::new(RefCell::new(self.clone()))), false));
+    pub fn get(class: &Rc<RefCell<Class>>, name: &Token) -> RuntimeResult<Literal> {
+        if let Some(Literal::Fun(method)) = class.borrow().find_static(&name.lexeme) {
+            return Ok(method.bind(Instance::Static(Rc::clone(class)), false));
         }
+        let names = class.borrow().static_names();
+        let hint = suggest::hint(&name.lexeme, names.iter().map(String::as_str));
         Err(RuntimeError::new(
+            ErrorKind::PropertyError,
             name.clone(),
-            &format!("Undefined property '{}'.", name.lexeme),
+            &format!("Undefined property '{}'.{}", name.lexeme, hint),
         ))
     }
 
     pub fn find_method(&self, name: &String) -> Option<Literal> {
-        return match self.methods.get(name) {
+        match self.methods.get(name) {
             Some(method) => Some(method.clone()),
             None => {
                 if let Some(class) = &self.super_class {
@@ -75,6 +115,35 @@ impl Class {
             }
         }
     }
+
+    // The names `find_method` can find, superclasses included.
+    pub fn method_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.methods.keys().cloned().collect();
+        if let Some(class) = &self.super_class {
+            names.extend(class.borrow().method_names());
+        }
+        names
+    }
+
+    pub fn static_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.statics.keys().cloned().collect();
+        if let Some(class) = &self.super_class {
+            names.extend(class.borrow().static_names());
+        }
+        names
+    }
+
+    pub fn find_static(&self, name: &String) -> Option<Literal> {
+        match self.statics.get(name) {
+            Some(method) => Some(method.clone()),
+            None => {
+                if let Some(class) = &self.super_class {
+                    return class.borrow().find_static(name);
+                }
+                None
+            }
+        }
+    }
 }
 
 impl fmt::Display for Class {
@@ -1,4 +1,5 @@
-use crate::error::report::RuntimeError;
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::error::suggest;
 use crate::interpreter::interpreter::RuntimeResult;
 use crate::lexer::literal::Literal;
 use crate::lexer::token::Token;
@@ -8,7 +9,7 @@ use std::rc::Rc;
 
 type Enclosing = Option<Rc<RefCell<Environment>>>;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Environment {
     pub values: HashMap<String, Literal>,
     pub outer_scope: Enclosing,
@@ -40,10 +41,10 @@ impl Environment {
     }
 
     pub fn get_at(&self, name: &String, distance: usize) -> Option<Literal> {
-        match self.ancestor(distance)?.borrow().values.get(name) {
-            Some(value) => Some(value.clone()),
-            None => None,
+        if distance == 0 {
+            return self.values.get(name).cloned();
         }
+        self.ancestor(distance)?.borrow().values.get(name).cloned()
     }
 
     pub fn assign(&mut self, name: &Token, value: Literal) -> RuntimeResult<()> {
@@ -54,10 +55,7 @@ impl Environment {
         if let Some(enclosing) = &self.outer_scope {
             return enclosing.borrow_mut().assign(name, value);
         }
-        Err(RuntimeError::new(
-            name.clone(),
-            &format!("Undefined variable '{}'.", name.lexeme),
-        ))
+        Err(self.undefined(name))
     }
 
     pub fn assign_at(
@@ -66,19 +64,40 @@ impl Environment {
         value: Literal,
         distance: usize,
     ) -> RuntimeResult<()> {
+        if distance == 0 {
+            self.values.insert(name.lexeme.clone(), value);
+            return Ok(());
+        }
         if let Some(env) = self.ancestor(distance) {
             env.borrow_mut().values.insert(name.lexeme.clone(), value);
             return Ok(());
         }
-        return Err(RuntimeError::new(
+        Err(self.undefined(name))
+    }
+
+    // Names a missing variable, suggesting the closest one in scope.
+    pub fn undefined(&self, name: &Token) -> RuntimeError {
+        let names = self.names();
+        let hint = suggest::hint(&name.lexeme, names.iter().map(String::as_str));
+        RuntimeError::new(
+            ErrorKind::NameError,
             name.clone(),
-            &format!("Undefined variable '{}'.", name.lexeme),
-        ));
+            &format!("Undefined variable '{}'.{}", name.lexeme, hint),
+        )
+    }
+
+    // Every name visible from this scope, including the globals.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.values.keys().cloned().collect();
+        if let Some(enclosing) = &self.outer_scope {
+            names.extend(enclosing.borrow().names());
+        }
+        names
     }
 
     fn ancestor(&self, distance: usize) -> Option<Rc<RefCell<Environment>>> {
-        let mut environment = Rc::new(RefCell::new(self.clone()));
-        for _ in 0..distance {
+        let mut environment = Rc::clone(self.outer_scope.as_ref()?);
+        for _ in 1..distance {
             environment = match &Rc::clone(&environment).borrow().outer_scope {
                 Some(e) => Rc::clone(e),
                 None => return None,
@@ -1,14 +1,15 @@
 use crate::interpreter::environment::Environment;
-use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
-use crate::lexer::literal::{Instance, Literal};
+use crate::interpreter::interpreter::{Frame, Interpreter, RuntimeResult};
+use crate::lexer::literal::{next_id, Instance, Literal};
 use crate::lexer::token::Token;
 use crate::parser::statement::Stmt;
 use std::cell::RefCell;
 use std::fmt;
 use std::rc::Rc;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub struct Function {
+    pub id: usize,
     pub arity: usize,
     pub name: Option<Token>,
     params: Vec<Token>,
@@ -28,6 +29,7 @@ impl Function {
         let arity = params.len();
         let closure = Rc::clone(parent);
         Function {
+            id: next_id(),
             arity,
             name,
             params,
@@ -40,16 +42,40 @@ impl Function {
     pub fn call(
         &self,
         interpreter: &mut Interpreter,
+        call_site: &Token,
         args: &Vec<Literal>,
+    ) -> RuntimeResult<Literal> {
+        let name = match &self.name {
+            Some(name) => name.lexeme.clone(),
+            None => "lambda".to_string(),
+        };
+        self.call_as(interpreter, call_site, args, name)
+    }
+
+    // Like `call`, but with `function` as the name of its frame in the call
+    // stack. Constructors use the name of their class.
+    pub fn call_as(
+        &self,
+        interpreter: &mut Interpreter,
+        call_site: &Token,
+        args: &Vec<Literal>,
+        function: String,
     ) -> RuntimeResult<Literal> {
         let mut env = Environment::new(Some(Rc::clone(&self.closure)));
-        for i in 0..self.arity {
-            env.define(self.params[i].lexeme.clone(), args[i].clone());
+        for (param, arg) in self.params.iter().zip(args) {
+            env.define(param.lexeme.clone(), arg.clone());
         }
+        interpreter.call_stack.push(Frame {
+            function,
+            line: call_site.line,
+        });
         let in_initializer = interpreter.in_initializer;
         interpreter.in_initializer = self.is_initializer;
-        interpreter.visit_block_stmt(&self.body, Some(env))?;
+        let result = interpreter.visit_block_stmt(&self.body, Some(env));
         interpreter.in_initializer = in_initializer;
+        let result = result.map_err(|err| err.with_stack(&interpreter.call_stack));
+        interpreter.call_stack.pop();
+        result?;
 
         if self.is_initializer {
             if let Some(instance) = self.closure.borrow().get_at(&"this".to_string(), 0) {
@@ -70,9 +96,9 @@ impl Function {
             self.is_initializer,
         );
         if is_getter {
-            return Literal::Get(function);
+            return Literal::Get(Rc::new(function));
         }
-        Literal::Fun(function)
+        Literal::Fun(Rc::new(function))
     }
 }
 
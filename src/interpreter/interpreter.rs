@@ -1,14 +1,28 @@
-use crate::error::report::{runtime_report, RuntimeError};
+use crate::error::emitter::{Emitter, ErrorFormat};
+use crate::error::report::{ErrorKind, RuntimeError};
 use crate::interpreter::class::Class;
 use crate::interpreter::environment::Environment;
 use crate::interpreter::function::Function;
+use crate::interpreter::object::Object;
 use crate::lexer::literal::{Instance, Literal};
-use crate::lexer::token::{Token, TokenType};
-use crate::parser::expression::{Expr, Expression};
+use crate::lexer::token::{Span, Token, TokenType};
+use crate::parser::expression::Expr;
 use crate::parser::statement::{Declarations, Stmt};
+use crate::stdlib::fs::FsAccess;
+use crate::stdlib::random::Rng;
+use crate::stdlib::{self, list, map, regex, string};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::Instant;
+
+// A call in progress: the name of what was called and the line it was
+// called from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub function: String,
+    pub line: u32,
+}
 
 pub struct Interpreter {
     pub environment: Rc<RefCell<Environment>>,
@@ -16,6 +30,15 @@ pub struct Interpreter {
     locals: HashMap<Token, usize>,
     return_value: Literal,
     pub in_initializer: bool,
+    pub fs_access: FsAccess,
+    pub exit_code: Option<i32>,
+    pub had_runtime_error: bool,
+    pub rng: Rng,
+    pub start_time: Instant,
+    pub call_stack: Vec<Frame>,
+    pub emitter: Emitter,
+    // Missing-member hooks currently running, by object and hook name.
+    running_hooks: Vec<(usize, &'static str)>,
 }
 
 pub type RuntimeResult<T> = Result<T, RuntimeError>;
@@ -27,12 +50,21 @@ impl Interpreter {
         let locals = HashMap::new();
         let return_value = Literal::Nothing;
         let in_initializer = false;
+        stdlib::load(&mut globals.borrow_mut());
         Interpreter {
             globals,
             environment,
             locals,
             return_value,
             in_initializer,
+            fs_access: FsAccess::Denied,
+            exit_code: None,
+            had_runtime_error: false,
+            rng: Rng::from_time(),
+            start_time: Instant::now(),
+            call_stack: Vec::new(),
+            emitter: Emitter::new(ErrorFormat::Human, "<stdin>"),
+            running_hooks: Vec::new(),
         }
     }
 
@@ -40,15 +72,17 @@ impl Interpreter {
         self.locals.insert(name.clone(), depth);
     }
 
-    pub fn interpret(&mut self, program: &Declarations) {
-        dbg!(program);
+    // Runtime errors are printed with a snippet of `source`, which can be
+    // left empty to print them without one.
+    pub fn interpret(&mut self, program: &Declarations, source: &str) {
         for stmt in program {
-            let result = self
-                .visit_stmt(stmt)
-                .map_err(|err| runtime_report(err))
-                .err();
-            if let Some(e) = result {
-                println!("{}", e);
+            if let Err(e) = self.visit_stmt(stmt) {
+                if let Some(code) = e.exit_code() {
+                    self.exit_code = Some(code);
+                    return;
+                }
+                self.emitter.emit(&e.diagnostic(), source);
+                self.had_runtime_error = true;
             }
         }
     }
@@ -66,14 +100,19 @@ impl Interpreter {
             Stmt::Function(name, params, body) => self.visit_function_stmt(name, params, body),
             Stmt::Getter(name, _) => {
                 return Err(RuntimeError::new(
+                    ErrorKind::TypeError,
                     name.clone(),
                     &format!("{} getter require a class.", name.lexeme),
                 ))
             }
             Stmt::Return(keyword, value) => self.visit_return_stmt(keyword, value),
-            Stmt::Class(name, methods, super_class) => {
-                self.visit_class_stmt(name, methods, super_class)
+            Stmt::Class(name, methods, statics, super_class) => {
+                self.visit_class_stmt(name, methods, statics, super_class)
             }
+            Stmt::Record(name, fields, methods, statics) => {
+                self.visit_record_stmt(name, fields, methods, statics)
+            }
+            Stmt::Error(span) => Err(unparsed(*span)),
         }?;
         Ok(())
     }
@@ -100,13 +139,13 @@ impl Interpreter {
             return Ok(());
         }
         let value = match *initializer {
-            Expr::Literal(Literal::Nothing) => Literal::Nothing,
+            Expr::Literal(Literal::Nothing, _) => Literal::Nothing,
             _ => self.visit_expr(initializer)?,
         };
         self.environment
             .borrow_mut()
             .define(name.lexeme.clone(), value);
-        return Ok(());
+        Ok(())
     }
 
     pub fn visit_block_stmt(
@@ -155,17 +194,17 @@ impl Interpreter {
         if let Some(else_branch) = else_branch {
             return self.visit_stmt(else_branch);
         }
-        return Ok(());
+        Ok(())
     }
 
     fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> RuntimeResult<()> {
         let mut result = self.visit_expr(condition)?;
         while self.is_truthy(&result) {
-            self.visit_stmt(&body)?;
+            self.visit_stmt(body)?;
             if self.return_value != Literal::Nothing {
                 return Ok(());
             }
-            result = self.visit_expr(&condition)?;
+            result = self.visit_expr(condition)?;
         }
         Ok(())
     }
@@ -173,19 +212,19 @@ impl Interpreter {
     fn visit_function_stmt(
         &mut self,
         name: &Token,
-        params: &Vec<Token>,
+        params: &[Token],
         body: &Declarations,
     ) -> RuntimeResult<()> {
         let function = Function::new(
             Some(name.clone()),
-            params.clone(),
+            params.to_vec(),
             body.clone(),
             &self.environment,
             false,
         );
         self.environment
             .borrow_mut()
-            .define(name.lexeme.clone(), Literal::Fun(function));
+            .define(name.lexeme.clone(), Literal::Fun(Rc::new(function)));
         Ok(())
     }
 
@@ -199,7 +238,7 @@ impl Interpreter {
             return Ok(());
         }
         self.return_value = match value {
-            Expr::Literal(Literal::Nothing) => Literal::Nothing,
+            Expr::Literal(Literal::Nothing, _) => Literal::Nothing,
             _ => self.visit_expr(value)?,
         };
         Ok(())
@@ -209,15 +248,24 @@ impl Interpreter {
         &mut self,
         name: &Token,
         methods: &Vec<Stmt>,
+        statics: &Vec<Stmt>,
         super_class: &Option<Expr>,
     ) -> RuntimeResult<()> {
         let parent_class = if let Some(super_class) = super_class {
             match self.visit_expr(super_class)? {
-                Literal::Class(class) => Some(Rc::new(RefCell::new(class))),
+                Literal::Class(class) if class.borrow().is_record() => {
+                    return Err(RuntimeError::new(
+                        ErrorKind::TypeError,
+                        name.clone(),
+                        "Cannot inherit from a record.",
+                    ))
+                }
+                Literal::Class(class) => Some(class),
                 _ => {
                     return Err(RuntimeError::new(
+                        ErrorKind::TypeError,
                         name.clone(),
-                        &format!("Superclass must be a class."),
+                        "Superclass must be a class.",
                     ))
                 }
             }
@@ -233,40 +281,69 @@ impl Interpreter {
             self.environment.borrow_mut().define("super".to_string(), parent_class);
         }
 
+        let class_methods = self.class_methods(methods, false);
+        let static_methods = self.class_methods(statics, true);
+        let class = Literal::Class(Rc::new(RefCell::new(Class::new(
+            name.lexeme.clone(),
+            class_methods,
+            static_methods,
+            parent_class,
+        ))));
+        if super_class.is_some() {
+            let current_env = Rc::clone(&self.environment);
+            self.environment = match &current_env.borrow().outer_scope {
+                Some(enclosing) => Rc::clone(enclosing),
+                None => Rc::clone(&self.globals)
+            };
+        }
+
+        self.environment.borrow_mut().assign(name, class)
+    }
+
+    fn visit_record_stmt(
+        &mut self,
+        name: &Token,
+        fields: &[Token],
+        methods: &Vec<Stmt>,
+        statics: &Vec<Stmt>,
+    ) -> RuntimeResult<()> {
+        let fields = fields.iter().map(|field| field.lexeme.clone()).collect();
+        let record_methods = self.class_methods(methods, false);
+        let static_methods = self.class_methods(statics, true);
+        let record = Class::record(name.lexeme.clone(), fields, record_methods, static_methods);
+        self.environment.borrow_mut().define(
+            name.lexeme.clone(),
+            Literal::Class(Rc::new(RefCell::new(record))),
+        );
+        Ok(())
+    }
+
+    fn class_methods(&self, methods: &Vec<Stmt>, is_static: bool) -> HashMap<String, Literal> {
         let mut class_methods = HashMap::new();
         for method in methods {
             if let Stmt::Function(name, params, body) = method {
-                let function = Literal::Fun(Function::new(
+                let function = Literal::Fun(Rc::new(Function::new(
                     Some(name.clone()),
                     params.clone(),
                     body.clone(),
                     &self.environment,
-                    name.lexeme == "init",
-                ));
+                    !is_static && name.lexeme == "init",
+                )));
                 class_methods.insert(name.lexeme.clone(), function);
             }
 
             if let Stmt::Getter(name, body) = method {
-                let function = Literal::Get(Function::new(
+                let function = Literal::Get(Rc::new(Function::new(
                     Some(name.clone()),
                     Vec::new(),
                     body.clone(),
                     &self.environment,
                     false,
-                ));
+                )));
                 class_methods.insert(name.lexeme.clone(), function);
             }
         }
-        let class = Literal::Class(Class::new(name.lexeme.clone(), class_methods, parent_class));
-        if let Some(_) = super_class {
-            let current_env = Rc::clone(&self.environment);
-            self.environment = match &current_env.borrow().outer_scope {
-                Some(enclosing) => Rc::clone(&enclosing),
-                None => Rc::clone(&self.globals)
-            };
-        }
-
-        self.environment.borrow_mut().assign(name, class)
+        class_methods
     }
 
     fn visit_expr(&mut self, expr: &Expr) -> RuntimeResult<Literal> {
@@ -276,17 +353,18 @@ impl Interpreter {
             Expr::Binary(left, operator, right) => self.visit_binary_expr(left, operator, right),
             Expr::Logical(left, operator, right) => self.visit_logical_expr(left, operator, right),
             Expr::Ternary(left, middle, right) => self.visit_ternary_expr(left, middle, right),
-            Expr::Grouping(group) => self.visit_grouping_expr(group),
+            Expr::Grouping(group, _) => self.visit_grouping_expr(group),
             Expr::Unary(operator, right) => self.visit_unary_expr(operator, right),
             Expr::Call(callee, right_paren, arguments) => {
                 self.visit_call_expr(callee, right_paren, arguments)
             }
-            Expr::Lambda(args, body) => self.visit_lambda_expr(args, body),
+            Expr::Lambda(args, body, _) => self.visit_lambda_expr(args, body),
             Expr::Get(instance, name) => self.visit_get_expr(instance, name),
             Expr::Set(object, name, value) => self.visit_set_expr(object, name, value),
             Expr::This(name) => self.visit_this_expr(name),
             Expr::Super(keyword, method) => self.visit_super_expr(keyword, method),
-            Expr::Literal(value) => self.visit_literal(value.clone()),
+            Expr::Literal(value, _) => self.visit_literal(value.clone()),
+            Expr::Error(span) => Err(unparsed(*span)),
         }
     }
 
@@ -311,10 +389,7 @@ impl Interpreter {
         };
         match value {
             Some(v) => Ok(v.clone()),
-            None => Err(RuntimeError::new(
-                name.clone(),
-                &format!("Undefined variable '{}'.", name.lexeme),
-            )),
+            None => Err(self.environment.borrow().undefined(name)),
         }
     }
 
@@ -336,10 +411,11 @@ impl Interpreter {
             | TokenType::GreaterEqual
             | TokenType::Less
             | TokenType::LessEqual => self.calculate_bool(&l, operator, &r),
-            TokenType::BangEqual => Ok(Literal::Bool(!self.is_equal(l, r))),
-            TokenType::EqualEqual => Ok(Literal::Bool(self.is_equal(l, r))),
+            TokenType::BangEqual => Ok(Literal::Bool(!self.is_equal(l, operator, r)?)),
+            TokenType::EqualEqual => Ok(Literal::Bool(self.is_equal(l, operator, r)?)),
             TokenType::Comma => Ok(r),
             _ => Err(RuntimeError::new(
+                ErrorKind::TypeError,
                 operator.clone(),
                 "Unknown binary operator.",
             )),
@@ -373,13 +449,13 @@ impl Interpreter {
     ) -> RuntimeResult<Literal> {
         let l = self.visit_expr(left)?;
         match self.is_truthy(&l) {
-            true => return self.visit_expr(middle),
-            false => return self.visit_expr(right),
+            true => self.visit_expr(middle),
+            false => self.visit_expr(right),
         }
     }
 
     fn visit_grouping_expr(&mut self, group: &Expr) -> RuntimeResult<Literal> {
-        self.visit_expr(&group)
+        self.visit_expr(group)
     }
 
     fn visit_unary_expr(&mut self, operator: &Token, expr: &Expr) -> RuntimeResult<Literal> {
@@ -387,21 +463,21 @@ impl Interpreter {
         match operator.typ {
             TokenType::Minus => {
                 if let Literal::Number(num) = right {
-                    return Ok(Literal::Number(-num));
+                    Ok(Literal::Number(-num))
                 } else {
-                    return Err(RuntimeError::new(
+                    Err(RuntimeError::new(
+                        ErrorKind::TypeError,
                         operator.clone(),
                         "Cannot make non-number negative.",
-                    ));
+                    ))
                 }
             }
-            TokenType::Bang => return Ok(Literal::Bool(!self.is_truthy(&right))),
-            _ => {
-                return Err(RuntimeError::new(
-                    operator.clone(),
-                    "Uknown unary operator.",
-                ))
-            }
+            TokenType::Bang => Ok(Literal::Bool(!self.is_truthy(&right))),
+            _ => Err(RuntimeError::new(
+                ErrorKind::TypeError,
+                operator.clone(),
+                "Uknown unary operator.",
+            )),
         }
     }
 
@@ -409,64 +485,215 @@ impl Interpreter {
         &mut self,
         callee: &Expr,
         right_paren: &Token,
-        arguments: &Vec<Expression>,
+        arguments: &[Expr],
+    ) -> RuntimeResult<Literal> {
+        let callee = match callee {
+            Expr::Get(object, name) => {
+                let instance = self.visit_expr(object)?;
+                if let Literal::Instance(Instance::Dynamic(receiver)) = &instance {
+                    let invoke = self.hook(receiver, "__invoke");
+                    if let (false, Some(invoke)) =
+                        (receiver.borrow().has_member(&name.lexeme), invoke)
+                    {
+                        let evaluated_args = self.visit_arguments(arguments)?;
+                        let args = vec![
+                            Literal::Str(name.lexeme.clone()),
+                            Literal::List(Rc::new(RefCell::new(evaluated_args))),
+                        ];
+                        return self.call_hook(receiver, "__invoke", &invoke, right_paren, &args);
+                    }
+                }
+                self.get_property(instance, name)?
+            }
+            _ => self.visit_expr(callee)?,
+        };
+        let evaluated_args = self.visit_arguments(arguments)?;
+        self.call(callee, right_paren, &evaluated_args)
+    }
+
+    pub fn call(
+        &mut self,
+        callee: Literal,
+        right_paren: &Token,
+        args: &Vec<Literal>,
     ) -> RuntimeResult<Literal> {
-        let callee = self.visit_expr(callee)?;
-        let mut evaluated_args = Vec::new();
-        for arg in arguments {
-            evaluated_args.push(self.visit_expr(arg)?);
-        }
         match callee {
-            Literal::Fun(function) => {
-                if arguments.len() != function.arity {
+            Literal::Fun(function) => self.call_function(&function, right_paren, args),
+            Literal::Native(native) => {
+                if !native.accepts(args.len()) {
                     return Err(RuntimeError::new(
+                        ErrorKind::ArityError,
                         right_paren.clone(),
                         "Wrong number of arguments.",
                     ));
                 }
-                function.call(self, &evaluated_args)?;
-                let value = self.return_value.clone();
-                self.return_value = Literal::Nothing;
-                return Ok(value);
+                native.call(self, right_paren, args)
+            }
+            Literal::Class(class) | Literal::Instance(Instance::Static(class)) => {
+                if args.len() != class.borrow().arity() {
+                    return Err(RuntimeError::new(
+                        ErrorKind::ArityError,
+                        right_paren.clone(),
+                        "Wrong number of arguments.",
+                    ));
+                }
+                Class::call(&class, self, right_paren, args)
+            }
+            Literal::Instance(Instance::Dynamic(object)) => {
+                let call = object
+                    .borrow()
+                    .class
+                    .borrow()
+                    .find_method(&"call".to_string());
+                if let Some(Literal::Fun(method)) = call {
+                    if let Literal::Fun(bound) = method.bind(Instance::Dynamic(object), false) {
+                        return self.call_function(&bound, right_paren, args);
+                    }
+                }
+                Err(RuntimeError::new(
+                    ErrorKind::TypeError,
+                    right_paren.clone(),
+                    "Can only call objects that define a 'call' method.",
+                ))
             }
-            Literal::Class(class) => class.call(self, &evaluated_args),
             _ => Err(RuntimeError::new(
+                ErrorKind::TypeError,
                 right_paren.clone(),
                 "Can only call functions and classes.",
             )),
         }
     }
 
-    fn visit_lambda_expr(
-        &self,
-        params: &Vec<Token>,
-        body: &Declarations,
+    fn visit_arguments(&mut self, arguments: &[Expr]) -> RuntimeResult<Vec<Literal>> {
+        let mut evaluated_args = Vec::new();
+        for arg in arguments {
+            evaluated_args.push(self.visit_expr(arg)?);
+        }
+        Ok(evaluated_args)
+    }
+
+    fn call_function(
+        &mut self,
+        function: &Function,
+        right_paren: &Token,
+        args: &Vec<Literal>,
     ) -> RuntimeResult<Literal> {
-        let function = Function::new(None, params.clone(), body.clone(), &self.environment, false);
-        Ok(Literal::Fun(function))
+        if args.len() != function.arity {
+            return Err(RuntimeError::new(
+                ErrorKind::ArityError,
+                right_paren.clone(),
+                "Wrong number of arguments.",
+            ));
+        }
+        function.call(self, right_paren, args)?;
+        let value = self.return_value.clone();
+        self.return_value = Literal::Nothing;
+        Ok(value)
+    }
+
+    fn visit_lambda_expr(&self, params: &[Token], body: &Declarations) -> RuntimeResult<Literal> {
+        let function = Function::new(
+            None,
+            params.to_vec(),
+            body.clone(),
+            &self.environment,
+            false,
+        );
+        Ok(Literal::Fun(Rc::new(function)))
     }
 
     fn visit_get_expr(&mut self, expr: &Expr, name: &Token) -> RuntimeResult<Literal> {
         let instance = self.visit_expr(expr)?;
+        self.get_property(instance, name)
+    }
+
+    // A missing-member hook is not used again for its own object while it is
+    // running, so that the hook can read and write the object's own fields.
+    fn hook(&self, object: &Rc<RefCell<Object>>, hook: &'static str) -> Option<Rc<Function>> {
+        let key = (Rc::as_ptr(object) as usize, hook);
+        if self.running_hooks.contains(&key) {
+            return None;
+        }
+        Object::hook(object, hook)
+    }
+
+    fn call_hook(
+        &mut self,
+        object: &Rc<RefCell<Object>>,
+        hook: &'static str,
+        function: &Function,
+        token: &Token,
+        args: &Vec<Literal>,
+    ) -> RuntimeResult<Literal> {
+        self.running_hooks.push((Rc::as_ptr(object) as usize, hook));
+        let result = self.call_function(function, token, args);
+        self.running_hooks.pop();
+        result
+    }
+
+    fn get_property(&mut self, instance: Literal, name: &Token) -> RuntimeResult<Literal> {
         if let Literal::Instance(Instance::Dynamic(object)) = instance {
-            // This is grabbing the wrong function
-            let result = object.borrow().get(name)?;
+            let result = match (Object::get(&object, name), self.hook(&object, "__get")) {
+                (Ok(result), _) => result,
+                (Err(_), Some(hook)) => {
+                    let args = vec![Literal::Str(name.lexeme.clone())];
+                    return self.call_hook(&object, "__get", &hook, name, &args);
+                }
+                (Err(err), None) => return Err(err),
+            };
             if let Literal::Get(getter) = result {
-                getter.call(self, &Vec::new())?;
+                getter.call(self, name, &Vec::new())?;
                 let value = self.return_value.clone();
                 self.return_value = Literal::Nothing;
                 return Ok(value);
             }
-            if let Literal::Fun(fun) = result {
-                dbg!(&fun.name);
-                return Ok(Literal::Fun(fun));
-            }
             return Ok(result)
         }
-        if let Literal::Class(class) = instance {
-            return class.get(name);
+        if let Literal::Class(class) | Literal::Instance(Instance::Static(class)) = instance {
+            return Class::get(&class, name);
+        }
+        if let Literal::Str(s) = instance {
+            if let Some(method) = string::method(&name.lexeme) {
+                return Ok(Literal::Native(Rc::new(method.bind(Literal::Str(s)))));
+            }
+            return Err(RuntimeError::new(
+                ErrorKind::PropertyError,
+                name.clone(),
+                &format!("Undefined property '{}'.", name.lexeme),
+            ));
+        }
+        if let Literal::List(list) = instance {
+            if let Some(method) = list::method(&name.lexeme) {
+                return Ok(Literal::Native(Rc::new(method.bind(Literal::List(list)))));
+            }
+            return Err(RuntimeError::new(
+                ErrorKind::PropertyError,
+                name.clone(),
+                &format!("Undefined property '{}'.", name.lexeme),
+            ));
+        }
+        if let Literal::Map(entries) = instance {
+            if let Some(method) = map::method(&name.lexeme) {
+                return Ok(Literal::Native(Rc::new(method.bind(Literal::Map(entries)))));
+            }
+            return Err(RuntimeError::new(
+                ErrorKind::PropertyError,
+                name.clone(),
+                &format!("Undefined property '{}'.", name.lexeme),
+            ));
+        }
+        if let Literal::Regex(pattern) = instance {
+            if let Some(method) = regex::method(&name.lexeme) {
+                return Ok(Literal::Native(Rc::new(method.bind(Literal::Regex(pattern)))));
+            }
+            return Err(RuntimeError::new(
+                ErrorKind::PropertyError,
+                name.clone(),
+                &format!("Undefined property '{}'.", name.lexeme),
+            ));
         }
         Err(RuntimeError::new(
+            ErrorKind::PropertyError,
             name.clone(),
             "Only instances have properties.",
         ))
@@ -481,10 +708,18 @@ impl Interpreter {
         let instance = self.visit_expr(left)?;
         if let Literal::Instance(Instance::Dynamic(object)) = instance {
             let value = self.visit_expr(right)?;
+            if !object.borrow().fields.contains_key(&name.lexeme) {
+                if let Some(hook) = self.hook(&object, "__set") {
+                    let args = vec![Literal::Str(name.lexeme.clone()), value.clone()];
+                    self.call_hook(&object, "__set", &hook, name, &args)?;
+                    return Ok(value);
+                }
+            }
             let result = object.borrow_mut().set(name, value)?;
             return Ok(result);
         }
         Err(RuntimeError::new(
+            ErrorKind::PropertyError,
             name.clone(),
             "Only instances have properties.",
         ))
@@ -499,6 +734,7 @@ impl Interpreter {
         match value {
             Some(v) => Ok(v.clone()),
             None => Err(RuntimeError::new(
+                ErrorKind::NameError,
                 name.clone(),
                 &format!("Undefined variable '{}'.", name.lexeme),
             )),
@@ -512,14 +748,17 @@ impl Interpreter {
             let object = self.environment.borrow().get_at(&"this".to_string(), d - 1);
             if let Some(Literal::Class(class)) = super_class {
                 if let Some(Literal::Instance(instance)) = object {
-                    let method = class.find_method(&method.lexeme);
+                    let method = match instance {
+                        Instance::Static(_) => class.borrow().find_static(&method.lexeme),
+                        Instance::Dynamic(_) => class.borrow().find_method(&method.lexeme),
+                    };
                     if let Some(Literal::Fun(function)) = method {
                         return Ok(function.bind(instance, false));
                     }
                 }
             }
         }
-        Err(RuntimeError::new(method.clone(), &format!("Undefined property '{}'.", method.lexeme)))
+        Err(RuntimeError::new(ErrorKind::PropertyError, method.clone(), &format!("Undefined property '{}'.", method.lexeme)))
     }
 
     fn visit_literal(&self, value: Literal) -> RuntimeResult<Literal> {
@@ -534,16 +773,16 @@ impl Interpreter {
     ) -> RuntimeResult<Literal> {
         match left {
             Literal::Number(l) => match right {
-                Literal::Number(r) => return Ok(Literal::Number(r + l)),
-                Literal::Str(r) => return Ok(Literal::Str(format!("{}{}", l, r))),
-                _ => return Err(RuntimeError::new(operator.clone(), "Cannot add operands.")),
+                Literal::Number(r) => Ok(Literal::Number(r + l)),
+                Literal::Str(r) => Ok(Literal::Str(format!("{}{}", l, r))),
+                _ => Err(RuntimeError::new(ErrorKind::TypeError, operator.clone(), "Cannot add operands.")),
             },
             Literal::Str(l) => match right {
-                Literal::Number(r) => return Ok(Literal::Str(format!("{}{}", l, r))),
-                Literal::Str(r) => return Ok(Literal::Str(format!("{}{}", l, r))),
-                _ => return Err(RuntimeError::new(operator.clone(), "Cannot add operands.")),
+                Literal::Number(r) => Ok(Literal::Str(format!("{}{}", l, r))),
+                Literal::Str(r) => Ok(Literal::Str(format!("{}{}", l, r))),
+                _ => Err(RuntimeError::new(ErrorKind::TypeError, operator.clone(), "Cannot add operands.")),
             },
-            _ => Err(RuntimeError::new(operator.clone(), "Cannot add operands.")),
+            _ => Err(RuntimeError::new(ErrorKind::TypeError, operator.clone(), "Cannot add operands.")),
         }
     }
 
@@ -561,6 +800,7 @@ impl Interpreter {
                     TokenType::Slash => {
                         if *r == 0.0 {
                             return Err(RuntimeError::new(
+                                ErrorKind::ZeroDivision,
                                 operator.clone(),
                                 "Cannot divide by zero.",
                             ));
@@ -569,6 +809,7 @@ impl Interpreter {
                     }
                     _ => {
                         return Err(RuntimeError::new(
+                            ErrorKind::TypeError,
                             operator.clone(),
                             "Uknown operator for numbers.",
                         ))
@@ -577,6 +818,7 @@ impl Interpreter {
             }
         }
         Err(RuntimeError::new(
+            ErrorKind::TypeError,
             operator.clone(),
             "Operand must be a number",
         ))
@@ -597,6 +839,7 @@ impl Interpreter {
                     TokenType::LessEqual => return Ok(Literal::Bool(l <= r)),
                     _ => {
                         return Err(RuntimeError::new(
+                            ErrorKind::TypeError,
                             operator.clone(),
                             "Uknown operator for numbers.",
                         ))
@@ -605,6 +848,7 @@ impl Interpreter {
             }
         }
         Err(RuntimeError::new(
+            ErrorKind::TypeError,
             operator.clone(),
             "Cannot compare non-booleans.",
         ))
@@ -618,7 +862,171 @@ impl Interpreter {
         }
     }
 
-    fn is_equal(&self, left: Literal, right: Literal) -> bool {
-        left == right
+    fn is_equal(&mut self, left: Literal, operator: &Token, right: Literal) -> RuntimeResult<bool> {
+        self.is_equal_within(left, operator, right, &mut Vec::new())
+    }
+
+    // `visited` holds the pairs of records already being compared. A pair
+    // seen again is taken as equal, so records that hold themselves compare
+    // without recursing forever; any difference still makes the whole
+    // comparison false.
+    fn is_equal_within(
+        &mut self,
+        left: Literal,
+        operator: &Token,
+        right: Literal,
+        visited: &mut Vec<(Option<usize>, Option<usize>)>,
+    ) -> RuntimeResult<bool> {
+        if let Literal::Instance(Instance::Dynamic(object)) = &left {
+            let equals = object
+                .borrow()
+                .class
+                .borrow()
+                .find_method(&"equals".to_string());
+            if let Some(Literal::Fun(method)) = equals {
+                if let Literal::Fun(bound) =
+                    method.bind(Instance::Dynamic(Rc::clone(object)), false)
+                {
+                    let result = self.call_function(&bound, operator, &vec![right])?;
+                    return Ok(self.is_truthy(&result));
+                }
+            }
+            if let Literal::Instance(Instance::Dynamic(other)) = &right {
+                let class = Rc::clone(&object.borrow().class);
+                let fields = class.borrow().fields.clone();
+                if let Some(fields) = fields {
+                    if !Rc::ptr_eq(&class, &other.borrow().class) {
+                        return Ok(false);
+                    }
+                    let pair = (left.identity(), right.identity());
+                    if visited.contains(&pair) {
+                        return Ok(true);
+                    }
+                    visited.push(pair);
+                    for field in &fields {
+                        let l = object.borrow().fields.get(field).cloned();
+                        let r = other.borrow().fields.get(field).cloned();
+                        let equal = match (l, r) {
+                            (Some(l), Some(r)) => self.is_equal_within(l, operator, r, visited)?,
+                            _ => false,
+                        };
+                        if !equal {
+                            return Ok(false);
+                        }
+                    }
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(left == right)
+    }
+}
+
+// Tolerant parsing leaves error nodes where the source did not parse. Such
+// programs are normally rejected before they run; this covers callers that
+// run them anyway.
+fn unparsed(span: Span) -> RuntimeError {
+    let token = Token::new(TokenType::Identifier, String::new(), span, 0);
+    RuntimeError::new(
+        ErrorKind::SyntaxError,
+        token,
+        "Cannot run code that failed to parse.",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::scanner::Scanner;
+    use crate::parser::parser::Parser;
+    use crate::parser::resolver::Resolver;
+
+    // Runs `source` and returns the value of the global `name`.
+    fn run(source: &str, name: &str) -> Literal {
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens, false);
+        parser.parse();
+        assert!(parser.errors.is_empty());
+        let mut interpreter = Interpreter::new();
+        Resolver::new(&mut interpreter)
+            .resolve(&parser.statements)
+            .unwrap();
+        interpreter.interpret(&parser.statements, source);
+        assert!(!interpreter.had_runtime_error);
+        let value = interpreter.globals.borrow().get(&name.to_string());
+        value.unwrap()
+    }
+
+    #[test]
+    fn reports_code_that_failed_to_parse() {
+        // An expression and a statement that did not parse.
+        let mut scanner = Scanner::new("print 1 +;\n) 2;");
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens, false);
+        parser.tolerant = true;
+        parser.parse();
+        assert_eq!(parser.errors.len(), 2);
+        let mut interpreter = Interpreter::new();
+        for statement in &parser.statements[..2] {
+            let err = interpreter.visit_stmt(statement).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::SyntaxError);
+        }
+        assert!(interpreter.visit_stmt(&parser.statements[2]).is_ok());
+    }
+
+    #[test]
+    fn compares_and_prints_records_that_hold_themselves() {
+        let source = "record R(a, b);
+            fun cyclic(a) { var r = R(a, nil); r.b = r; return r; }
+            var r = cyclic(1);
+            var equal = r == cyclic(1);
+            var different = r == cyclic(2);
+            var l = R(1, nil);
+            var m = R(1, l);
+            l.b = m;
+            var mutual = l == m;
+            var text = r.toString() + \" \" + l.toString();";
+        assert_eq!(run(source, "equal"), Literal::Bool(true));
+        assert_eq!(run(source, "different"), Literal::Bool(false));
+        assert_eq!(run(source, "mutual"), Literal::Bool(true));
+        let text = "R(a: 1, b: R(...)) R(a: 1, b: R(a: 1, b: R(...)))";
+        assert_eq!(run(source, "text"), Literal::Str(text.to_string()));
+    }
+
+    #[test]
+    fn runs_hooks_through_this_but_not_inside_themselves() {
+        let source = "var log = \"\";
+            class Bag {
+                __get(name) { log = log + \"get \" + name + \";\"; return name + \"?\"; }
+                __set(name, value) { log = log + \"set \" + name + \";\"; this.last = value; }
+                __invoke(name, args) { return name + \"!\"; }
+                read() { return this.size; }
+                write() { this.size = 3; }
+                act() { return this.shake(); }
+            }
+            var bag = Bag();
+            bag.write();
+            var read = bag.read();
+            var act = bag.act();
+            var last = bag.last;";
+        let log = "set size;get size;";
+        assert_eq!(run(source, "log"), Literal::Str(log.to_string()));
+        assert_eq!(run(source, "last"), Literal::Number(3.0));
+        assert_eq!(run(source, "read"), Literal::Str("size?".to_string()));
+        assert_eq!(run(source, "act"), Literal::Str("shake!".to_string()));
+    }
+
+    #[test]
+    fn numbers_objects_in_creation_order() {
+        let source = "class Point {}
+            var a = Point();
+            var b = Point();
+            var stable = id(a) == id(a);
+            var ordered = id(Point) < id(a) and id(a) < id(b);
+            var whole = id(b) == math.floor(id(b));";
+        assert_eq!(run(source, "stable"), Literal::Bool(true));
+        assert_eq!(run(source, "ordered"), Literal::Bool(true));
+        assert_eq!(run(source, "whole"), Literal::Bool(true));
     }
 }
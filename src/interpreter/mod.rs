@@ -1,5 +1,7 @@
 pub mod class;
 pub mod environment;
 pub mod function;
+#[allow(clippy::module_inception)]
 pub mod interpreter;
+pub mod native;
 pub mod object;
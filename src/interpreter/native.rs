@@ -0,0 +1,72 @@
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::lexer::literal::{next_id, Literal};
+use crate::lexer::token::Token;
+use std::fmt;
+
+pub type NativeFn = fn(&mut Interpreter, &Token, &[Literal]) -> RuntimeResult<Literal>;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Arity {
+    Fixed(usize),
+    Variadic(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Native {
+    pub id: usize,
+    pub name: String,
+    pub arity: Arity,
+    function: NativeFn,
+    receiver: Option<Literal>,
+}
+
+impl Native {
+    pub fn new(name: &str, arity: Arity, function: NativeFn) -> Native {
+        Native {
+            id: next_id(),
+            name: name.to_string(),
+            arity,
+            function,
+            receiver: None,
+        }
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        match self.arity {
+            Arity::Fixed(arity) => count == arity,
+            Arity::Variadic(min) => count >= min,
+        }
+    }
+
+    pub fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        paren: &Token,
+        args: &[Literal],
+    ) -> RuntimeResult<Literal> {
+        match &self.receiver {
+            Some(receiver) => {
+                let mut bound_args = vec![receiver.clone()];
+                bound_args.extend_from_slice(args);
+                (self.function)(interpreter, paren, &bound_args)
+            }
+            None => (self.function)(interpreter, paren, args),
+        }
+    }
+
+    pub fn bind(&self, receiver: Literal) -> Native {
+        Native {
+            id: next_id(),
+            name: self.name.clone(),
+            arity: self.arity,
+            function: self.function,
+            receiver: Some(receiver),
+        }
+    }
+}
+
+impl fmt::Display for Native {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
@@ -1,58 +1,147 @@
-use crate::error::report::RuntimeError;
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::error::suggest;
 use crate::interpreter::class::Class;
+use crate::interpreter::function::Function;
 use crate::interpreter::interpreter::RuntimeResult;
-use crate::lexer::literal::{Instance, Literal};
+use crate::lexer::literal::{next_id, Instance, Literal};
 use crate::lexer::token::Token;
+use crate::stdlib::record;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub struct Object {
+    pub id: usize,
     pub class: Rc<RefCell<Class>>,
     pub fields: HashMap<String, Literal>,
 }
 
 impl Object {
-    pub fn new(class: Class) -> Object {
+    pub fn new(class: Rc<RefCell<Class>>) -> Object {
         Object {
-            class: Rc::new(RefCell::new(class)),
+            id: next_id(),
+            class,
             fields: HashMap::new(),
         }
     }
 
-    pub fn get(&self, name: &Token) -> RuntimeResult<Literal> {
-        if let Some(value) = self.fields.get(&name.lexeme) {
+    pub fn get(object: &Rc<RefCell<Object>>, name: &Token) -> RuntimeResult<Literal> {
+        if let Some(value) = object.borrow().fields.get(&name.lexeme) {
             return Ok(value.clone());
         }
-        if let Some(Literal::Fun(method)) = self.class.borrow().find_method(&name.lexeme) {
-            return Ok(method.bind(
-                Instance::Dynamic(Rc::new(RefCell::new(self.clone()))),
-                false,
-            ));
+        let method = object.borrow().class.borrow().find_method(&name.lexeme);
+        if let Some(Literal::Fun(method)) = method {
+            return Ok(method.bind(Instance::Dynamic(Rc::clone(object)), false));
+        }
+        if let Some(Literal::Get(getter)) = method {
+            return Ok(getter.bind(Instance::Dynamic(Rc::clone(object)), true));
         }
-        if let Some(Literal::Get(getter)) = self.class.borrow().find_method(&name.lexeme) {
-            return Ok(getter.bind(Instance::Dynamic(Rc::new(RefCell::new(self.clone()))), true));
+        if object.borrow().has_record_method(&name.lexeme) {
+            if let Some(native) = record::method(&name.lexeme) {
+                let receiver = Literal::Instance(Instance::Dynamic(Rc::clone(object)));
+                return Ok(Literal::Native(Rc::new(native.bind(receiver))));
+            }
         }
+        let object = object.borrow();
+        let mut names = object.class.borrow().method_names();
+        names.extend(object.fields.keys().cloned());
+        let hint = suggest::hint(&name.lexeme, names.iter().map(String::as_str));
         Err(RuntimeError::new(
+            ErrorKind::PropertyError,
             name.clone(),
-            &format!("Undefined property '{}'.", name.lexeme),
+            &format!("Undefined property '{}'.{}", name.lexeme, hint),
         ))
     }
 
+    pub fn hook(object: &Rc<RefCell<Object>>, name: &str) -> Option<Rc<Function>> {
+        let method = object
+            .borrow()
+            .class
+            .borrow()
+            .find_method(&name.to_string());
+        if let Some(Literal::Fun(method)) = method {
+            if let Literal::Fun(bound) = method.bind(Instance::Dynamic(Rc::clone(object)), false) {
+                return Some(bound);
+            }
+        }
+        None
+    }
+
+    pub fn has_member(&self, name: &str) -> bool {
+        self.fields.contains_key(name)
+            || self.class.borrow().find_method(&name.to_string()).is_some()
+            || self.has_record_method(name)
+    }
+
+    pub fn has_record_method(&self, name: &str) -> bool {
+        let class = self.class.borrow();
+        class.is_record()
+            && record::method(name).is_some()
+            && class.find_method(&name.to_string()).is_none()
+    }
+
+    pub fn with(&self, paren: &Token, overrides: &[Literal]) -> RuntimeResult<Object> {
+        if !overrides.len().is_multiple_of(2) {
+            return Err(RuntimeError::new(
+                ErrorKind::TypeError,
+                paren.clone(),
+                "Expect field name and value pairs in 'with'.",
+            ));
+        }
+        let mut copy = self.clone();
+        for pair in overrides.chunks(2) {
+            match &pair[0] {
+                Literal::Str(field) if copy.fields.contains_key(field) => {
+                    copy.fields.insert(field.clone(), pair[1].clone());
+                }
+                field => {
+                    return Err(RuntimeError::new(
+                        ErrorKind::PropertyError,
+                        paren.clone(),
+                        &format!(
+                            "Record '{}' has no field {}.",
+                            self.class.borrow().name,
+                            field
+                        ),
+                    ))
+                }
+            }
+        }
+        Ok(copy)
+    }
+
     pub fn set(&mut self, name: &Token, value: Literal) -> RuntimeResult<Literal> {
         self.fields.insert(name.lexeme.clone(), value.clone());
         Ok(value)
     }
+
+    // Display with the `path` of Literal::write, so that a record holding
+    // itself prints as `Name(...)` instead of recursing forever.
+    pub fn write(&self, f: &mut fmt::Formatter, path: &mut Vec<usize>) -> fmt::Result {
+        let class = self.class.borrow();
+        let fields = match &class.fields {
+            Some(fields) => fields,
+            None => return write!(f, "<object {}>", class.name),
+        };
+        write!(f, "{}(", class.name)?;
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: ", field)?;
+            match self.fields.get(field) {
+                Some(value) => value.write(f, path)?,
+                None => write!(f, "nil")?,
+            }
+        }
+        write!(f, ")")
+    }
 }
 
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut string = "<object {}>".to_string();
-        for (key, value) in &self.fields {
-            string += &format!("{}: {},", key, value);
-        }
-        write!(f, "<object {}>", self.class.borrow().name)
+        self.write(f, &mut Vec::new())
     }
 }
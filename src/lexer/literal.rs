@@ -1,41 +1,205 @@
+use crate::error::diagnostic::quote;
 use crate::interpreter::class::Class;
 use crate::interpreter::function::Function;
+use crate::interpreter::native::Native;
 use crate::interpreter::object::Object;
+use crate::stdlib::regex::pattern::Regex;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::rc::Rc;
-#[derive(Debug, PartialEq, Clone)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+// Hands out the numbers that `id()` reports, so that they say nothing about
+// where an object lives in memory and are never reused.
+pub fn next_id() -> usize {
+    NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone)]
 pub enum Literal {
     Str(String),
     Number(f64),
     Bool(bool),
-    Fun(Function),
-    Get(Function),
-    Class(Class),
+    Fun(Rc<Function>),
+    Get(Rc<Function>),
+    Native(Rc<Native>),
+    Class(Rc<RefCell<Class>>),
     Instance(Instance),
+    List(Rc<RefCell<Vec<Literal>>>),
+    Map(Rc<RefCell<BTreeMap<String, Literal>>>),
+    Regex(Rc<Regex>),
     Nothing,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone)]
 pub enum Instance {
     Static(Rc<RefCell<Class>>),
     Dynamic(Rc<RefCell<Object>>),
 }
 
+impl Literal {
+    pub fn identity(&self) -> Option<usize> {
+        match self {
+            Literal::Fun(function) | Literal::Get(function) => {
+                Some(Rc::as_ptr(function) as *const u8 as usize)
+            }
+            Literal::Native(native) => Some(Rc::as_ptr(native) as *const u8 as usize),
+            Literal::Class(class) | Literal::Instance(Instance::Static(class)) => {
+                Some(Rc::as_ptr(class) as *const u8 as usize)
+            }
+            Literal::Instance(Instance::Dynamic(object)) => {
+                Some(Rc::as_ptr(object) as *const u8 as usize)
+            }
+            Literal::List(list) => Some(Rc::as_ptr(list) as *const u8 as usize),
+            Literal::Map(map) => Some(Rc::as_ptr(map) as *const u8 as usize),
+            Literal::Regex(regex) => Some(Rc::as_ptr(regex) as *const u8 as usize),
+            _ => None,
+        }
+    }
+
+    // The number `id()` reports for instances, classes and functions.
+    pub fn id(&self) -> Option<usize> {
+        match self {
+            Literal::Fun(function) | Literal::Get(function) => Some(function.id),
+            Literal::Native(native) => Some(native.id),
+            Literal::Class(class) | Literal::Instance(Instance::Static(class)) => {
+                Some(class.borrow().id)
+            }
+            Literal::Instance(Instance::Dynamic(object)) => Some(object.borrow().id),
+            _ => None,
+        }
+    }
+
+    // Like Display, but a container that is already on `path`, the
+    // identities of the containers being written, is printed as `[...]`,
+    // `{...}` or `Name(...)` so that one holding itself does not recurse
+    // forever.
+    pub fn write(&self, f: &mut fmt::Formatter, path: &mut Vec<usize>) -> fmt::Result {
+        let id = self.identity().unwrap_or_default();
+        match self {
+            Literal::List(_) if path.contains(&id) => write!(f, "[...]"),
+            Literal::Map(_) if path.contains(&id) => write!(f, "{{...}}"),
+            Literal::Instance(Instance::Dynamic(object)) if path.contains(&id) => {
+                write!(f, "{}(...)", object.borrow().class.borrow().name)
+            }
+            Literal::Instance(Instance::Dynamic(object)) => {
+                path.push(id);
+                object.borrow().write(f, path)?;
+                path.pop();
+                Ok(())
+            }
+            Literal::List(list) => {
+                path.push(id);
+                write!(f, "[")?;
+                for (i, item) in list.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.write(f, path)?;
+                }
+                path.pop();
+                write!(f, "]")
+            }
+            Literal::Map(map) => {
+                path.push(id);
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.borrow().iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: ", quote(key))?;
+                    value.write(f, path)?;
+                }
+                path.pop();
+                write!(f, "}}")
+            }
+            _ => write!(f, "{}", self),
+        }
+    }
+}
+
+impl PartialEq for Literal {
+    fn eq(&self, other: &Literal) -> bool {
+        match (self, other) {
+            (Literal::Str(l), Literal::Str(r)) => l == r,
+            (Literal::Number(l), Literal::Number(r)) => l == r,
+            (Literal::Bool(l), Literal::Bool(r)) => l == r,
+            (Literal::Nothing, Literal::Nothing) => true,
+            (Literal::Fun(_), Literal::Fun(_))
+            | (Literal::Get(_), Literal::Get(_))
+            | (Literal::Native(_), Literal::Native(_))
+            | (Literal::Class(_), Literal::Class(_))
+            | (Literal::Instance(_), Literal::Instance(_))
+            | (Literal::List(_), Literal::List(_))
+            | (Literal::Map(_), Literal::Map(_))
+            | (Literal::Regex(_), Literal::Regex(_)) => self.identity() == other.identity(),
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Literal::Str(s) => write!(f, "\"{}\"", s),
             Literal::Number(n) => write!(f, "{}", n),
             Literal::Bool(b) => write!(f, "{}", b),
-            Literal::Fun(function) => write!(f, "{}", function.to_string()),
-            Literal::Get(function) => write!(f, "getter {}", function.to_string()),
-            Literal::Class(class) => write!(f, "{}", class.to_string()),
+            Literal::Fun(function) => write!(f, "{}", function),
+            Literal::Get(function) => write!(f, "getter {}", function),
+            Literal::Native(native) => write!(f, "{}", native),
+            Literal::Class(class) => write!(f, "{}", class.borrow()),
             Literal::Instance(instance) => match instance {
-                Instance::Static(class) => write!(f, "{}", class.borrow().to_string()),
-                Instance::Dynamic(object) => write!(f, "{}", object.borrow().to_string()),
+                Instance::Static(class) => write!(f, "{}", class.borrow()),
+                Instance::Dynamic(_) => self.write(f, &mut Vec::new()),
             },
+            Literal::List(_) | Literal::Map(_) => self.write(f, &mut Vec::new()),
+            Literal::Regex(regex) => write!(f, "{}", regex),
             Literal::Nothing => write!(f, "nil"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(entries: Vec<(&str, Literal)>) -> Literal {
+        let entries = entries.into_iter().map(|(k, v)| (k.to_string(), v));
+        Literal::Map(Rc::new(RefCell::new(entries.collect())))
+    }
+
+    #[test]
+    fn prints_containers_that_hold_themselves() {
+        let list = Literal::List(Rc::new(RefCell::new(vec![Literal::Number(1.0)])));
+        let outer = map(vec![("list", list.clone())]);
+        if let (Literal::List(items), Literal::Map(entries)) = (&list, &outer) {
+            items.borrow_mut().push(list.clone());
+            entries
+                .borrow_mut()
+                .insert("self".to_string(), outer.clone());
+        }
+        assert_eq!(list.to_string(), "[1, [...]]");
+        assert_eq!(outer.to_string(), r#"{"list": [1, [...]], "self": {...}}"#);
+        // Break the cycles so the test does not leak.
+        if let (Literal::List(items), Literal::Map(entries)) = (&list, &outer) {
+            items.borrow_mut().clear();
+            entries.borrow_mut().clear();
+        }
+    }
+
+    #[test]
+    fn prints_shared_values_in_full() {
+        let shared = map(vec![("a", Literal::Bool(true))]);
+        let outer = Literal::List(Rc::new(RefCell::new(vec![shared.clone(), shared])));
+        assert_eq!(outer.to_string(), r#"[{"a": true}, {"a": true}]"#);
+    }
+
+    #[test]
+    fn quotes_map_keys() {
+        let value = map(vec![("say \"hi\"\n", Literal::Nothing)]);
+        assert_eq!(value.to_string(), r#"{"say \"hi\"\n": nil}"#);
+    }
+}
@@ -1,4 +1,11 @@
-use super::token::{Token, TokenType};
+use super::token::{Span, Token, TokenType};
+use crate::error::code;
+use crate::error::diagnostic::{Allow, Diagnostic, Phase, Severity};
+
+pub const KEYWORDS: [&str; 17] = [
+    "and", "class", "else", "false", "for", "fun", "if", "nil", "or", "print", "record", "return",
+    "super", "this", "true", "var", "while",
+];
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Kind {
     Str,
@@ -16,40 +23,73 @@ pub struct Scanner<'a> {
     current_kind: Kind,
     current_token_number: u32,
     line: u32,
-    errors: Vec<String>,
+    column: u32,
+    offset: usize,
+    start: Option<Span>,
+    end: usize,
+    comment_start: usize,
+    allows: Vec<Allow>,
+    errors: Vec<Diagnostic>,
 }
 
 impl<'a> Scanner<'a> {
-    pub fn new(source: &str) -> Scanner {
+    pub fn new(source: &str) -> Scanner<'_> {
         Scanner {
-            source: source,
+            source,
             tokens: Vec::new(),
             current_token: String::new(),
             current_kind: Kind::Nothing,
             current_token_number: 0,
             line: 1,
+            column: 1,
+            offset: 0,
+            start: None,
+            end: 0,
+            comment_start: 0,
+            allows: Vec::new(),
             errors: Vec::new(),
         }
     }
 
-    pub fn scan_tokens(&mut self) -> &Vec<Token> {
-        for c in self.source.chars() {
+    pub fn scan_tokens(&mut self) -> Result<&Vec<Token>, &Vec<Diagnostic>> {
+        for (offset, c) in self.source.char_indices() {
+            self.offset = offset;
             self.scan_token(c);
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.offset = self.source.len();
+        if self.current_kind == Kind::Comment {
+            self.end_line_comment();
         }
         self.add_saved_token('\0');
         self.tokens.push(Token::new(
             TokenType::Eof,
             String::new(),
-            self.line,
+            self.position(),
             self.current_token_number,
         ));
-        &self.tokens
+        if !self.errors.is_empty() {
+            return Err(&self.errors);
+        }
+        Ok(&self.tokens)
+    }
+
+    pub fn allows(&self) -> &Vec<Allow> {
+        &self.allows
     }
 
     fn scan_token(&mut self, c: char) {
         match &self.current_kind {
             Kind::Comment if c != '\n' => return,
-            Kind::MultiComment if !self.multi_comment_end(c) => return,
+            Kind::MultiComment => {
+                self.multi_comment(c);
+                return;
+            }
             Kind::Str => {
                 self.add_to_string(c);
                 return;
@@ -90,7 +130,7 @@ impl<'a> Scanner<'a> {
             '"' => self.add_to_string(c),
             c if valid_digit(c) => self.add_to_number(c),
             c if valid_identifier(c) => self.add_to_identifier(c),
-            _ => self.add_error(self.line, "Unexpected character"),
+            _ => self.unexpected_character(c),
         };
     }
 
@@ -103,15 +143,24 @@ impl<'a> Scanner<'a> {
     fn add_multi_token(&mut self, c: char) {
         self.add_saved_token(c);
         self.advance(c);
+        // Start a comment right away, so whatever follows the opening pair
+        // is not scanned as a token.
+        if self.current_token == "//" || self.current_token == "/*" {
+            self.add_comment();
+        }
     }
 
     fn add_to_string(&mut self, c: char) {
         if self.current_kind == Kind::Nothing {
             self.current_kind = Kind::Str;
+            // The span covers the quotes even though the lexeme does not.
+            self.start = Some(self.position());
+            self.end = self.offset + 1;
             return;
         }
 
         if c == '"' {
+            self.end = self.offset + 1;
             self.add_token(TokenType::Str);
             return;
         }
@@ -135,22 +184,16 @@ impl<'a> Scanner<'a> {
 
     fn add_saved_token(&mut self, c: char) {
         match &self.current_kind {
-            Kind::Str => self.add_error(self.line, "Unterminated string"),
-            Kind::Number => self.add_token(TokenType::Number),
+            Kind::Str => self.unterminated_string(),
+            Kind::Number => self.add_number(),
             Kind::Identifier => self.add_token(self.identifier_type()),
-            Kind::Comment => return,
-            Kind::MultiComment if self.current_token.as_str() != "*/" => return,
+            Kind::Comment | Kind::MultiComment => return,
             _ => (),
         };
 
         match self.current_token.as_str() {
             "/" if c != '/' && c != '*' => self.add_token(TokenType::Slash),
-            "*" if c != '/' && self.current_kind != Kind::MultiComment => {
-                self.add_token(TokenType::Star)
-            }
-            "*" if c != '/' && self.current_kind == Kind::MultiComment => {
-                self.current_token = String::new()
-            }
+            "*" if c != '/' => self.add_token(TokenType::Star),
             "=" if c != '=' => self.add_token(TokenType::Equal),
             "!" if c != '=' => self.add_token(TokenType::Bang),
             "<" if c != '=' => self.add_token(TokenType::Less),
@@ -159,28 +202,41 @@ impl<'a> Scanner<'a> {
             "==" => self.add_token(TokenType::EqualEqual),
             "<=" => self.add_token(TokenType::LessEqual),
             ">=" => self.add_token(TokenType::GreaterEqual),
-            "//" => self.add_comment(),
-            "/*" => self.add_comment(),
-            "*/" => self.end_comment(),
             "" => (),
             _ => (),
         };
     }
 
     fn add_token(&mut self, token_type: TokenType) {
+        let mut span = self.start.unwrap_or_else(|| self.position());
+        span.length = self.end - span.offset;
         self.tokens.push(Token::new(
             token_type,
             self.current_token.to_owned(),
-            self.line,
+            span,
             self.current_token_number,
         ));
-        self.current_token = String::new();
+        self.clear_token();
         self.current_kind = Kind::Nothing;
         self.current_token_number += 1;
     }
 
     fn advance(&mut self, c: char) {
+        if self.start.is_none() {
+            self.start = Some(self.position());
+        }
         self.current_token.push(c);
+        self.end = self.offset + c.len_utf8();
+    }
+
+    fn clear_token(&mut self) {
+        self.current_token = String::new();
+        self.start = None;
+    }
+
+    // Where the char being scanned sits, as an empty span.
+    fn position(&self) -> Span {
+        Span::new(self.offset, 0, self.line, self.column)
     }
 
     fn identifier_type(&self) -> TokenType {
@@ -195,6 +251,7 @@ impl<'a> Scanner<'a> {
             "nil" => TokenType::Nil,
             "or" => TokenType::Or,
             "print" => TokenType::Print,
+            "record" => TokenType::Record,
             "return" => TokenType::Return,
             "super" => TokenType::Super,
             "this" => TokenType::This,
@@ -206,56 +263,98 @@ impl<'a> Scanner<'a> {
     }
 
     fn white_space(&mut self, c: char) {
-        if c == '\n' {
-            self.new_line();
-        }
         self.add_saved_token(c);
+        if c == '\n' && self.current_kind == Kind::Comment {
+            self.end_line_comment();
+        }
     }
 
-    fn new_line(&mut self) {
-        self.line += 1;
-        if self.current_kind == Kind::Comment {
-            self.current_kind = Kind::Nothing;
+    // Line comments can carry `@allow(...)` annotations for the warnings.
+    fn end_line_comment(&mut self) {
+        let comment = &self.source[self.comment_start..self.offset];
+        if let Some(allow) = Allow::parse(self.line, comment) {
+            self.allows.push(allow);
         }
+        self.current_kind = Kind::Nothing;
     }
 
     fn add_comment(&mut self) {
         if self.current_token.as_str() == "//" {
             self.current_kind = Kind::Comment;
+            self.comment_start = self.offset + 1;
         } else {
             self.current_kind = Kind::MultiComment;
         }
-        self.current_token = String::new();
+        self.clear_token();
     }
 
     fn end_comment(&mut self) {
         self.current_kind = Kind::Nothing;
-        self.current_token = String::new();
+        self.clear_token();
     }
 
-    fn add_error(&mut self, line: u32, message: &str) {
-        self.errors.push(format!("{} {}", line, message));
+    fn add_number(&mut self) {
+        if self.current_token.parse::<f64>().is_ok() {
+            self.add_token(TokenType::Number);
+            return;
+        }
+        let mut span = self.start.unwrap_or_else(|| self.position());
+        span.length = self.end - span.offset;
+        let location = format!("at '{}'", self.current_token);
+        self.add_error(code::INVALID_NUMBER, span, &location, "Invalid number.");
+        self.clear_token();
+        self.current_kind = Kind::Nothing;
     }
 
-    fn multi_comment_end(&self, c: char) -> bool {
-        if self.current_token.as_str() == "*/" {
-            return true;
-        }
+    fn unexpected_character(&mut self, c: char) {
+        self.add_saved_token(c);
+        let mut span = self.position();
+        span.length = c.len_utf8();
+        let location = format!("at '{}'", c);
+        self.add_error(
+            code::UNEXPECTED_CHARACTER,
+            span,
+            &location,
+            "Unexpected character.",
+        );
+    }
 
-        if self.current_token.as_str() == "*" && c == '/' {
-            return true;
-        }
+    // Only reached at the end of the source, so the string runs to there.
+    fn unterminated_string(&mut self) {
+        let mut span = self.start.unwrap_or_else(|| self.position());
+        span.length = self.source.trim_end().len() - span.offset;
+        self.add_error(code::UNTERMINATED_STRING, span, "", "Unterminated string.");
+        self.clear_token();
+        self.current_kind = Kind::Nothing;
+    }
+
+    fn add_error(&mut self, code: &'static str, span: Span, location: &str, message: &str) {
+        self.errors.push(Diagnostic::new(
+            Severity::Error,
+            Phase::Lex,
+            code,
+            span,
+            location,
+            message,
+        ));
+    }
 
+    // Inside a block comment the token only remembers whether the last
+    // char was a '*', to spot the closing "*/".
+    fn multi_comment(&mut self, c: char) {
+        if self.current_token == "*" && c == '/' {
+            self.end_comment();
+            return;
+        }
+        self.clear_token();
         if c == '*' {
-            return true;
+            self.current_token.push(c);
         }
-
-        false
     }
 }
 
 fn valid_digit(c: char) -> bool {
-    c.is_digit(10) || c == '.'
+    c.is_ascii_digit() || c == '.'
 }
 
 fn valid_identifier(c: char) -> bool {
@@ -40,6 +40,7 @@ pub enum TokenType {
     Nil,
     Or,
     Print,
+    Record,
     Return,
     Super,
     This,
@@ -55,23 +56,71 @@ impl fmt::Display for TokenType {
     }
 }
 
+// A stretch of source text: its byte range, plus the line and (char based)
+// column where it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub offset: usize,
+    pub length: usize,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Span {
+    pub fn new(offset: usize, length: usize, line: u32, column: u32) -> Span {
+        Span {
+            offset,
+            length,
+            line,
+            column,
+        }
+    }
+
+    // From the start of this span to the end of `end`.
+    pub fn to(&self, end: &Span) -> Span {
+        let length = (end.offset + end.length).saturating_sub(self.offset);
+        Span::new(self.offset, length.max(self.length), self.line, self.column)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Token {
     pub typ: TokenType,
     pub lexeme: String,
     pub line: u32,
+    pub column: u32,
+    pub offset: usize,
+    pub length: usize,
     pub number: u32,
 }
 
 impl Token {
-    pub fn new(typ: TokenType, lexeme: String, line: u32, number: u32) -> Token {
+    pub fn new(typ: TokenType, lexeme: String, span: Span, number: u32) -> Token {
         Token {
             typ,
             lexeme,
-            line,
+            line: span.line,
+            column: span.column,
+            offset: span.offset,
+            length: span.length,
             number,
         }
     }
+
+    // Stands in for a token that tolerant parsing expected but did not find.
+    // It is empty and takes up no source text, just before `next`.
+    pub fn missing(typ: TokenType, next: &Token) -> Token {
+        let span = Span::new(next.offset, 0, next.line, next.column);
+        Token::new(typ, String::new(), span, next.number)
+    }
+
+    pub fn is_missing(&self) -> bool {
+        self.length == 0 && self.typ != TokenType::Eof
+    }
+
+    pub fn span(&self) -> Span {
+        Span::new(self.offset, self.length, self.line, self.column)
+    }
 }
 
 impl fmt::Display for Token {
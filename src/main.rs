@@ -2,64 +2,217 @@ mod error;
 mod interpreter;
 mod lexer;
 mod parser;
+mod stdlib;
 
+use error::diagnostic::Diagnostic;
+use error::emitter::{Emitter, ErrorFormat};
 use interpreter::interpreter::Interpreter;
 use lexer::scanner::Scanner;
 use parser::parser::Parser;
 use parser::resolver::Resolver;
 use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, Read, Write};
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+use stdlib::fs::FsAccess;
+use stdlib::random::Rng;
+
+// Exit statuses from sysexits.h.
+const EX_USAGE: i32 = 64;
+const EX_DATAERR: i32 = 65;
+const EX_NOINPUT: i32 = 66;
+const EX_SOFTWARE: i32 = 70;
+
+struct Options {
+    script: Option<String>,
+    args: Vec<String>,
+    fs_access: FsAccess,
+    seed: Option<u64>,
+    error_format: ErrorFormat,
+    deny_warnings: bool,
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    match args.len() {
-        2 => run_file(&args[1]),
-        1 => run_prompt(),
-        _ => println!("Usage: tlox [script]"),
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_options(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("Usage: tlox [--allow-fs[=dir,...]] [--seed n] [--error-format=human|json] [--warnings-as-errors] [script [args...]]");
+            process::exit(EX_USAGE);
+        }
+    };
+    let mut interpreter = Interpreter::new();
+    interpreter.fs_access = options.fs_access;
+    interpreter.emitter = Emitter::new(
+        options.error_format,
+        options.script.as_deref().unwrap_or("<stdin>"),
+    );
+    interpreter.emitter.deny_warnings = options.deny_warnings;
+    if let Some(seed) = options.seed {
+        interpreter.rng = Rng::new(seed);
     }
+    stdlib::process::define_args(&mut interpreter.globals.borrow_mut(), &options.args);
+    match options.script {
+        Some(script) => {
+            if let Err(code) = run_file(&mut interpreter, &script) {
+                exit_with(code);
+            }
+        }
+        None => run_prompt(&mut interpreter),
+    }
+    exit(&interpreter);
 }
 
-fn run_file(file_name: &str) {
-    let mut source_file = File::open(file_name).unwrap();
-    let mut input = String::new();
-    let mut interpreter = Interpreter::new();
-    source_file.read_to_string(&mut input).unwrap();
-    run(&mut interpreter, &input, false);
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        script: None,
+        args: Vec::new(),
+        fs_access: FsAccess::Denied,
+        seed: None,
+        error_format: ErrorFormat::Human,
+        deny_warnings: false,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if options.script.is_some() {
+            // Everything after the script belongs to the script.
+            options.args.push(arg.clone());
+        } else if arg == "--allow-fs" {
+            options.fs_access = FsAccess::All;
+        } else if let Some(dirs) = arg.strip_prefix("--allow-fs=") {
+            let dirs: Vec<&str> = dirs.split(',').collect();
+            options.fs_access = FsAccess::within(&dirs)
+                .map_err(|err| format!("Cannot allow access to '{}': {}", dirs.join(","), err))?;
+        } else if arg == "--seed" || arg.starts_with("--seed=") {
+            let seed = match arg.strip_prefix("--seed=") {
+                Some(seed) => seed,
+                None => args.next().ok_or("Expect a number after '--seed'.")?,
+            };
+            // Same conversion as seed(n), so both replay the same sequence.
+            let seed = seed
+                .parse::<i64>()
+                .map_err(|_| format!("Invalid seed '{}'.", seed))?;
+            options.seed = Some(seed as u64);
+        } else if arg == "--error-format" || arg.starts_with("--error-format=") {
+            let format = match arg.strip_prefix("--error-format=") {
+                Some(format) => format,
+                None => args
+                    .next()
+                    .ok_or("Expect 'human' or 'json' after '--error-format'.")?,
+            };
+            options.error_format = match format {
+                "human" => ErrorFormat::Human,
+                "json" => ErrorFormat::Json,
+                _ => return Err(format!("Unknown error format '{}'.", format)),
+            };
+        } else if arg == "--warnings-as-errors" {
+            options.deny_warnings = true;
+        } else if arg.starts_with("--") {
+            return Err(format!("Unknown option '{}'.", arg));
+        } else {
+            options.script = Some(arg.clone());
+        }
+    }
+    Ok(options)
+}
+
+fn run_file(interpreter: &mut Interpreter, file_name: &str) -> Result<(), i32> {
+    let input = match fs::read_to_string(file_name) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Could not read '{}': {}.", file_name, err);
+            return Err(EX_NOINPUT);
+        }
+    };
+    run(interpreter, &input, false)?;
+    // A call to exit() picks the status itself.
+    if interpreter.had_runtime_error && interpreter.exit_code.is_none() {
+        return Err(EX_SOFTWARE);
+    }
+    Ok(())
 }
 
-fn run_prompt() {
+fn run_prompt(interpreter: &mut Interpreter) {
     new_line();
-    let mut interpreter = Interpreter::new();
-    for line in io::stdin().lock().lines() {
-        match line {
-            Ok(input) => {
-                run(&mut interpreter, &input, true);
+    // Read one line at a time instead of holding the stdin lock, so that
+    // scripts typed at the prompt can read from stdin themselves.
+    loop {
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) => break,
+            Ok(_) => {
+                // Errors at the prompt are reported but do not end the session.
+                let _ = run(interpreter, input.trim_end_matches(&['\r', '\n'][..]), true);
+                if interpreter.exit_code.is_some() {
+                    break;
+                }
                 new_line();
             }
-            Err(error) => println!("Error reading line: {}", error),
+            Err(error) => eprintln!("Error reading line: {}", error),
         }
     }
 }
 
-fn run(interpreter: &mut Interpreter, source: &str, is_repl: bool) {
+// Fails with EX_DATAERR when the source does not compile. Runtime errors
+// are reported by the interpreter, which records that one happened.
+fn run(interpreter: &mut Interpreter, source: &str, is_repl: bool) -> Result<(), i32> {
     let mut scanner = Scanner::new(source);
-    let tokens = scanner.scan_tokens();
+    let tokens = match scanner.scan_tokens() {
+        Ok(tokens) => tokens,
+        Err(errors) => {
+            emit(interpreter, errors, source);
+            return Err(EX_DATAERR);
+        }
+    };
     let mut parser = Parser::new(tokens, is_repl);
     parser.parse();
-    if parser.errors.len() > 0 {
-        parser.errors.iter().for_each(|err| println!("{}", err));
-        return;
+    if !parser.errors.is_empty() {
+        emit(interpreter, &parser.errors, source);
+        return Err(EX_DATAERR);
     }
     let program = parser.statements;
+    let mut warnings = parser.warnings;
     let mut resolver = Resolver::new(interpreter);
-    match resolver.resolve(&program) {
-        Ok(()) => interpreter.interpret(&program),
-        Err(e) => println!("{}", e),
+    let mut diagnostics = resolver.resolve(&program).err().unwrap_or_default();
+    warnings.append(&mut resolver.warnings);
+    warnings.retain(|warning| !scanner.allows().iter().any(|allow| allow.permits(warning)));
+    let deny = interpreter.emitter.deny_warnings;
+    let failed = !diagnostics.is_empty() || (deny && !warnings.is_empty());
+    for warning in warnings {
+        diagnostics.push(if deny { warning.deny() } else { warning });
     }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.offset);
+    emit(interpreter, &diagnostics, source);
+    if failed {
+        return Err(EX_DATAERR);
+    }
+    // At the prompt a runtime error can come from a function typed on an
+    // earlier line, so only show snippets for scripts.
+    interpreter.interpret(&program, if is_repl { "" } else { source });
+    Ok(())
+}
+
+fn emit(interpreter: &Interpreter, diagnostics: &[Diagnostic], source: &str) {
+    for diagnostic in diagnostics {
+        interpreter.emitter.emit(diagnostic, source);
+    }
+}
+
+fn exit(interpreter: &Interpreter) {
+    if let Some(code) = interpreter.exit_code {
+        exit_with(code);
+    }
+}
+
+// A failed flush, for example when stdout is a pipe that was closed
+// early, must not turn the requested exit into a panic.
+fn exit_with(code: i32) -> ! {
+    let _ = io::stdout().flush();
+    process::exit(code);
 }
 
 fn new_line() {
     print!("> ");
-    io::stdout().flush().unwrap();
+    let _ = io::stdout().flush();
 }
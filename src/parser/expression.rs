@@ -1,5 +1,5 @@
 use crate::lexer::literal::Literal;
-use crate::lexer::token::Token;
+use crate::lexer::token::{Span, Token};
 use crate::parser::statement::Declarations;
 use std::fmt;
 
@@ -7,22 +7,44 @@ use std::fmt;
 pub enum Expr {
     Binary(Expression, Token, Expression),
     Ternary(Expression, Expression, Expression),
-    Grouping(Expression),
-    Literal(Literal),
+    Grouping(Expression, Span),
+    Literal(Literal, Span),
     Logical(Expression, Token, Expression),
     Unary(Token, Expression),
     Variable(Token),
     Assign(Token, Expression),
-    Call(Expression, Token, Vec<Box<Expr>>),
-    Lambda(Vec<Token>, Declarations),
+    Call(Expression, Token, Vec<Expr>),
+    Lambda(Vec<Token>, Declarations, Span),
     Get(Expression, Token),
     Set(Expression, Token, Expression),
     This(Token),
-    Super(Token, Token)
+    Super(Token, Token),
+    // Where tolerant parsing expected an expression but could not parse one.
+    Error(Span),
 }
 
 pub type Expression = Box<Expr>;
 
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Binary(left, _, right) | Expr::Logical(left, _, right) => {
+                left.span().to(&right.span())
+            }
+            Expr::Ternary(left, _, right) => left.span().to(&right.span()),
+            Expr::Grouping(_, span) | Expr::Literal(_, span) | Expr::Lambda(_, _, span) => *span,
+            Expr::Unary(operator, right) => operator.span().to(&right.span()),
+            Expr::Variable(name) | Expr::This(name) => name.span(),
+            Expr::Assign(name, value) => name.span().to(&value.span()),
+            Expr::Call(callee, paren, _) => callee.span().to(&paren.span()),
+            Expr::Get(object, name) => object.span().to(&name.span()),
+            Expr::Set(object, _, value) => object.span().to(&value.span()),
+            Expr::Super(keyword, method) => keyword.span().to(&method.span()),
+            Expr::Error(span) => *span,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum FunctionType {
     Function,
@@ -1,4 +1,5 @@
 pub mod expression;
+#[allow(clippy::module_inception)]
 pub mod parser;
 pub mod resolver;
 pub mod statement;
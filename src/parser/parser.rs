@@ -1,47 +1,92 @@
 use super::expression::{Expr, Expression, FunctionType};
 use super::statement::{Declarations, Stmt};
-use crate::error::report::error;
+use crate::error::code;
+use crate::error::diagnostic::{Diagnostic, Phase};
+use crate::error::suggest;
 use crate::lexer::literal::Literal;
+use crate::lexer::scanner::KEYWORDS;
 use crate::lexer::token::{Token, TokenType};
+use std::collections::HashSet;
 
 #[derive(Debug)]
 pub struct Parser<'a> {
     tokens: &'a Vec<Token>,
     current: usize,
     is_repl: bool,
-    pub errors: Vec<String>,
+    // Tolerant parsing never gives up on a statement: missing tokens are
+    // inserted and anything else it cannot parse becomes an `Error` node, so
+    // `statements` covers the whole source even when there are errors. It is
+    // off by default and meant for editor tools; the command line parses
+    // strictly and reports one error per broken statement.
+    pub tolerant: bool,
+    // Set after a tolerant parse error until the parser is back in step,
+    // to leave out the errors that follow from the first one.
+    panicking: bool,
+    // Names declared so far and where the current statement starts, so that
+    // keywords are only suggested for typos like `fucn f() {}`.
+    declared: HashSet<String>,
+    statement_start: usize,
+    pub errors: Vec<Diagnostic>,
+    pub warnings: Vec<Diagnostic>,
     pub statements: Declarations,
 }
 
-type ParseResult<T> = Result<T, String>;
+type ParseResult<T> = Result<T, Diagnostic>;
 
 impl<'a> Parser<'a> {
-    pub fn new(tokens: &Vec<Token>, is_repl: bool) -> Parser {
+    pub fn new(tokens: &Vec<Token>, is_repl: bool) -> Parser<'_> {
         Parser {
-            tokens: tokens,
+            tokens,
             current: 0,
-            is_repl: is_repl,
+            is_repl,
+            tolerant: false,
+            panicking: false,
+            declared: HashSet::new(),
+            statement_start: 0,
             errors: Vec::new(),
+            warnings: Vec::new(),
             statements: Vec::new(),
         }
     }
 
     pub fn parse(&mut self) {
         while !self.is_at_end() {
-            match self.declaration() {
+            if let Some(Stmt::Return(..)) = self.statements.last() {
+                self.unreachable();
+            }
+            match self.next_declaration() {
                 Ok(statement) => self.statements.push(statement),
                 Err(err) => self.synchronize(err),
             }
         }
     }
 
+    // A tolerant parse can fail to use up any tokens, when the next one
+    // cannot start a declaration. It is then skipped as an `Error` node so
+    // that the parser moves on.
+    fn next_declaration(&mut self) -> ParseResult<Stmt> {
+        let start = self.current;
+        let statement = self.declaration()?;
+        if self.current > start {
+            return Ok(statement);
+        }
+        Ok(Stmt::Error(self.advance().span()))
+    }
+
     fn declaration(&mut self) -> ParseResult<Stmt> {
+        self.statement_start = self.current;
+        if self.at_declaration() {
+            self.panicking = false;
+        }
         if self.matches(&[TokenType::Var]) {
             return self.var_declaration();
         }
         if self.matches(&[TokenType::Class]) {
             return self.class_declaration();
         }
+        if self.matches(&[TokenType::Record]) {
+            return self.record_declaration();
+        }
         if self.matches(&[TokenType::Fun]) {
             return self.function(FunctionType::Function);
         }
@@ -49,42 +94,67 @@ impl<'a> Parser<'a> {
     }
 
     fn var_declaration(&mut self) -> ParseResult<Stmt> {
-        let name = self.consume(TokenType::Identifier, "Expect variable name.")?;
+        let name = self.declared_name("Expect variable name.")?;
         let initializer = match self.matches(&[TokenType::Equal]) {
             true => self.expression()?,
-            false => Box::new(Expr::Literal(Literal::Nothing)),
+            false => Box::new(Expr::Literal(Literal::Nothing, name.span())),
         };
         self.consume(TokenType::SemiColon, "Expect ';' after value.")?;
         Ok(Stmt::Var(name, initializer))
     }
 
     fn class_declaration(&mut self) -> ParseResult<Stmt> {
-        let name = self.consume(TokenType::Identifier, "Expect class name.")?;
+        let name = self.declared_name("Expect class name.")?;
         let mut super_class = None;
         if self.matches(&[TokenType::Less]) {
             self.consume(TokenType::Identifier, "Expect superclass name")?;
             super_class = Some(Expr::Variable(self.previous()));
         }
-        self.consume(TokenType::LeftBrace, "Expect '{' before class body")?;
-        let mut methods = Vec::new();
-        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-            methods.push(self.class_function()?);
-        }
-        self.consume(TokenType::RightBrace, "Expect '}' after class body")?;
-        Ok(Stmt::Class(name, methods, Box::new(super_class)))
+        let left_brace = self.consume(TokenType::LeftBrace, "Expect '{' before class body")?;
+        let (methods, statics) = self.class_body()?;
+        self.consume_closing(&left_brace, "Expect '}' after class body")?;
+        Ok(Stmt::Class(name, methods, statics, Box::new(super_class)))
     }
 
-    fn class_function(&mut self) -> ParseResult<Stmt> {
-        let kind = if self.matches(&[TokenType::Class]) {
-            FunctionType::Static
+    fn record_declaration(&mut self) -> ParseResult<Stmt> {
+        let name = self.declared_name("Expect record name.")?;
+        self.consume(TokenType::LeftParen, "Expect '(' after record name.")?;
+        let fields = self.function_arguments(Vec::new())?;
+        self.consume(TokenType::RightParen, "Expect ')' after record fields.")?;
+        let (mut methods, mut statics) = (Vec::new(), Vec::new());
+        if self.matches(&[TokenType::LeftBrace]) {
+            let left_brace = self.previous();
+            let body = self.class_body()?;
+            methods = body.0;
+            statics = body.1;
+            self.consume_closing(&left_brace, "Expect '}' after record body")?;
         } else {
-            FunctionType::Method
-        };
-        self.function(kind)
+            self.consume(TokenType::SemiColon, "Expect ';' after record declaration.")?;
+        }
+        Ok(Stmt::Record(name, fields, methods, statics))
+    }
+
+    fn class_body(&mut self) -> ParseResult<(Vec<Stmt>, Vec<Stmt>)> {
+        let mut methods = Vec::new();
+        let mut statics = Vec::new();
+        while !self.check(TokenType::RightBrace) && !self.is_at_end() {
+            if self.matches(&[TokenType::Class]) {
+                statics.push(self.function(FunctionType::Static)?);
+            } else if self.check(TokenType::Identifier) {
+                methods.push(self.function(FunctionType::Method)?);
+            } else {
+                // A stray token becomes an error node by itself, instead of
+                // the start of a made-up method that swallows what follows.
+                let err = self.parse_error(code::EXPECTED_TOKEN, "Expect method name.");
+                self.recover(err, ())?;
+                methods.push(Stmt::Error(self.advance().span()));
+            }
+        }
+        Ok((methods, statics))
     }
 
     fn function(&mut self, mut kind: FunctionType) -> ParseResult<Stmt> {
-        let name = self.consume(TokenType::Identifier, &format!("Expect {} name.", kind))?;
+        let name = self.declared_name(&format!("Expect {} name.", kind))?;
         let mut params = Vec::new();
         match kind {
             FunctionType::Function | FunctionType::Static => {
@@ -114,18 +184,23 @@ impl<'a> Parser<'a> {
 
     fn function_arguments(&mut self, mut params: Vec<Token>) -> ParseResult<Vec<Token>> {
         if !self.check(TokenType::RightParen) {
-            params.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
+            params.push(self.declared_name("Expect parameter name.")?);
             while self.matches(&[TokenType::Comma]) {
                 if params.len() > 254 {
-                    return Err(self.parse_error("Cannot have more than 255 arguments."));
+                    let err = self.parse_error(
+                        code::TOO_MANY_ARGUMENTS,
+                        "Cannot have more than 255 arguments.",
+                    );
+                    self.recover(err, ())?;
                 }
-                params.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
+                params.push(self.declared_name("Expect parameter name.")?);
             }
         }
         Ok(params)
     }
 
     fn statement(&mut self) -> ParseResult<Stmt> {
+        self.statement_start = self.current;
         if self.matches(&[TokenType::Print]) {
             return self.print_statement();
         }
@@ -147,12 +222,17 @@ impl<'a> Parser<'a> {
         self.expression_statement()
     }
 
+    // Expects the opening '{' to have just been consumed.
     fn block(&mut self) -> ParseResult<Declarations> {
+        let left_brace = self.previous();
         let mut statements = Vec::new();
         while !self.check(TokenType::RightBrace) && !self.is_at_end() {
-            statements.push(self.declaration()?);
+            if let Some(Stmt::Return(..)) = statements.last() {
+                self.unreachable();
+            }
+            statements.push(self.next_declaration()?);
         }
-        self.consume(TokenType::RightBrace, "Expect '}' after block.")?;
+        self.consume_closing(&left_brace, "Expect '}' after block.")?;
         Ok(statements)
     }
 
@@ -188,6 +268,7 @@ impl<'a> Parser<'a> {
     }
 
     fn for_statement(&mut self) -> ParseResult<Stmt> {
+        let keyword = self.previous();
         self.consume(TokenType::LeftParen, "Expect '(' after 'for'.")?;
         let initializer = if self.matches(&[TokenType::SemiColon]) {
             None
@@ -231,7 +312,10 @@ impl<'a> Parser<'a> {
         };
         body = match condition {
             Some(expr) => Stmt::While(expr, Box::new(body)),
-            None => Stmt::While(Box::new(Expr::Literal(Literal::Bool(true))), Box::new(body)),
+            None => Stmt::While(
+                Box::new(Expr::Literal(Literal::Bool(true), keyword.span())),
+                Box::new(body),
+            ),
         };
         if let Some(stmt) = initializer {
             body = Stmt::Block(vec![stmt, body]);
@@ -244,7 +328,7 @@ impl<'a> Parser<'a> {
         let value = if !self.check(TokenType::SemiColon) {
             self.expression()?
         } else {
-            Box::new(Expr::Literal(Literal::Nothing))
+            Box::new(Expr::Literal(Literal::Nothing, keyword.span()))
         };
         self.consume(TokenType::SemiColon, "Expect ';' after value.")?;
         Ok(Stmt::Return(keyword, value))
@@ -276,13 +360,23 @@ impl<'a> Parser<'a> {
     fn assignment(&mut self) -> ParseResult<Expression> {
         let expr = self.or()?;
         if self.matches(&[TokenType::Equal]) {
+            let equals = self.previous();
             let value = self.assignment()?;
             match *expr {
                 Expr::Variable(name) => return Ok(Box::new(Expr::Assign(name.clone(), value))),
                 Expr::Get(object, name) => {
                     return Ok(Box::new(Expr::Set(object.clone(), name.clone(), value)))
                 }
-                _ => return Err(self.parse_error("Invalid assignment target.")),
+                target => {
+                    let err = error(
+                        &equals,
+                        code::INVALID_ASSIGNMENT_TARGET,
+                        "Invalid assignment target.",
+                    )
+                    .spanning(target.span());
+                    let span = target.span().to(&value.span());
+                    return self.recover(err, Box::new(Expr::Error(span)));
+                }
             }
         }
         Ok(expr)
@@ -392,12 +486,16 @@ impl<'a> Parser<'a> {
     fn finish_call(&mut self, expr: Box<Expr>) -> ParseResult<Expression> {
         let mut arguments = Vec::new();
         if !self.check(TokenType::RightParen) {
-            arguments.push(self.call_argument()?);
+            arguments.push(*self.call_argument()?);
             while self.matches(&[TokenType::Comma]) {
                 if arguments.len() >= 255 {
-                    return Err(self.parse_error("Cannot have more than 255 arguments."));
+                    let err = self.parse_error(
+                        code::TOO_MANY_ARGUMENTS,
+                        "Cannot have more than 255 arguments.",
+                    );
+                    self.recover(err, ())?;
                 }
-                arguments.push(self.call_argument()?);
+                arguments.push(*self.call_argument()?);
             }
         }
         let paren = self.consume(TokenType::RightParen, "Expect ')' after arguments")?;
@@ -408,61 +506,83 @@ impl<'a> Parser<'a> {
         if self.matches(&[TokenType::Fun]) {
             return self.lambda();
         }
-        self.expression()
+        self.assignment()
     }
 
     fn lambda(&mut self) -> ParseResult<Expression> {
+        let keyword = self.previous();
         self.consume(
             TokenType::LeftParen,
-            &format!("Expect '(' after lambda declaration."),
+            "Expect '(' after lambda declaration.",
         )?;
         let mut params = Vec::new();
         if !self.check(TokenType::RightParen) {
-            params.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
+            params.push(self.declared_name("Expect parameter name.")?);
             while self.matches(&[TokenType::Comma]) {
                 if params.len() > 254 {
-                    return Err(self.parse_error("Cannot have more than 255 arguments."));
+                    let err = self.parse_error(
+                        code::TOO_MANY_ARGUMENTS,
+                        "Cannot have more than 255 arguments.",
+                    );
+                    self.recover(err, ())?;
                 }
-                params.push(self.consume(TokenType::Identifier, "Expect parameter name.")?);
+                params.push(self.declared_name("Expect parameter name.")?);
             }
         }
         self.consume(TokenType::RightParen, "Expect ')' after parameters")?;
         self.consume(
             TokenType::LeftBrace,
-            &format!("Expect '{{' before lambda body."),
+            "Expect '{' before lambda body.",
         )?;
         let body = self.block()?;
-        Ok(Box::new(Expr::Lambda(params, body)))
+        let span = keyword.span().to(&self.previous().span());
+        Ok(Box::new(Expr::Lambda(params, body, span)))
     }
 
     fn primary(&mut self) -> ParseResult<Expression> {
         if self.matches(&[TokenType::False]) {
-            return Ok(Box::new(Expr::Literal(Literal::Bool(false))));
+            return Ok(Box::new(Expr::Literal(
+                Literal::Bool(false),
+                self.previous().span(),
+            )));
         }
 
         if self.matches(&[TokenType::True]) {
-            return Ok(Box::new(Expr::Literal(Literal::Bool(true))));
+            return Ok(Box::new(Expr::Literal(
+                Literal::Bool(true),
+                self.previous().span(),
+            )));
         }
 
         if self.matches(&[TokenType::Nil]) {
-            return Ok(Box::new(Expr::Literal(Literal::Nothing)));
+            return Ok(Box::new(Expr::Literal(
+                Literal::Nothing,
+                self.previous().span(),
+            )));
         }
 
         if self.matches(&[TokenType::Number]) {
-            let num = self.previous().lexeme.parse().unwrap();
-            return Ok(Box::new(Expr::Literal(Literal::Number(num))));
+            let token = self.previous();
+            let num = token.lexeme.parse().unwrap();
+            return Ok(Box::new(Expr::Literal(Literal::Number(num), token.span())));
         }
 
         if self.matches(&[TokenType::Str]) {
-            return Ok(Box::new(Expr::Literal(Literal::Str(
-                self.previous().lexeme.clone(),
-            ))));
+            let token = self.previous();
+            return Ok(Box::new(Expr::Literal(
+                Literal::Str(token.lexeme.clone()),
+                token.span(),
+            )));
         }
 
         if self.matches(&[TokenType::LeftParen]) {
+            let left_paren = self.previous();
             let expr = self.expression()?;
             match self.consume(TokenType::RightParen, "Expect ')' after expression.") {
-                Ok(_) => return Ok(Box::new(Expr::Grouping(expr))),
+                Ok(right_paren) => {
+                    let span = left_paren.span().to(&right_paren.span());
+                    return Ok(Box::new(Expr::Grouping(expr, span)));
+                }
                 Err(message) => return Err(message),
             }
         }
@@ -482,41 +602,115 @@ impl<'a> Parser<'a> {
             return Ok(Box::new(Expr::Super(keyword, method)));
         }
 
-        Err(self.parse_error("Expect expression."))
+        let err = self.parse_error(code::EXPECTED_EXPRESSION, "Expect expression.");
+        let span = self.peek().span();
+        self.recover(err, Box::new(Expr::Error(span)))
     }
 
     fn consume(&mut self, token_type: TokenType, message: &str) -> ParseResult<Token> {
         if self.check(token_type) {
             return Ok(self.advance().clone());
         }
-        Err(self.parse_error(message))
+        let err = self.parse_error(code::EXPECTED_TOKEN, message);
+        let missing = Token::missing(token_type, self.peek());
+        self.recover(err, missing)
+    }
+
+    // Like `consume` for a closing brace, except that running into the end
+    // of the file reports the whole unterminated region from `open` on.
+    fn consume_closing(&mut self, open: &Token, message: &str) -> ParseResult<Token> {
+        if self.is_at_end() {
+            let span = open.span().to(&self.peek().span());
+            let err = self
+                .parse_error(code::UNCLOSED_BLOCK, message)
+                .spanning(span);
+            let missing = Token::missing(TokenType::RightBrace, self.peek());
+            return self.recover(err, missing);
+        }
+        self.consume(TokenType::RightBrace, message)
     }
 
-    fn synchronize(&mut self, err: String) {
+    // Tolerant parsing records `err` and carries on with `fallback` in place
+    // of what it expected. Otherwise the error ends the statement.
+    fn recover<T>(&mut self, err: Diagnostic, fallback: T) -> ParseResult<T> {
+        if !self.tolerant {
+            return Err(err);
+        }
+        if !self.panicking {
+            self.errors.push(err);
+            self.panicking = true;
+        }
+        Ok(fallback)
+    }
+
+    fn synchronize(&mut self, err: Diagnostic) {
         self.errors.push(err);
         self.advance();
         while !self.is_at_end() {
             if self.previous().typ == TokenType::SemiColon {
                 return;
             }
-            match self.peek().typ {
-                TokenType::Class
+            if self.at_declaration() {
+                return;
+            }
+            self.advance();
+        }
+    }
+
+    // Whether the next token starts a declaration or statement, where the
+    // parser can pick up again after an error.
+    fn at_declaration(&self) -> bool {
+        matches!(
+            self.peek().typ,
+            TokenType::Class
+                | TokenType::Record
                 | TokenType::Fun
                 | TokenType::Var
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
-                | TokenType::Return => return,
-                _ => (),
-            };
-            self.advance();
+                | TokenType::Return
+        )
+    }
+
+    // Called before the statement that follows a return in the same block,
+    // so only the first unreachable statement is reported. It is pointed out
+    // by its first token.
+    fn unreachable(&mut self) {
+        let warning = Diagnostic::warning(
+            Phase::Parse,
+            code::UNREACHABLE_CODE,
+            self.peek().span(),
+            "Unreachable code.",
+        );
+        self.warnings.push(warning);
+    }
+
+    fn declared_name(&mut self, message: &str) -> ParseResult<Token> {
+        let name = self.consume(TokenType::Identifier, message)?;
+        self.declared.insert(name.lexeme.clone());
+        Ok(name)
+    }
+
+    // An undeclared identifier that starts a statement and is close to a
+    // keyword was most likely meant to be that keyword, as in `fucn f() {}`.
+    fn parse_error(&self, code: &'static str, message: &str) -> Diagnostic {
+        let starts_statement = self.current > 0 && self.current - 1 == self.statement_start;
+        if starts_statement && self.previous().typ == TokenType::Identifier {
+            let typo = self.previous().lexeme;
+            if self.declared.contains(&typo) {
+                return error(self.peek(), code, message);
+            }
+            if let Some(keyword) = suggest::closest(&typo, KEYWORDS.iter().copied()) {
+                let message = format!(
+                    "{} Did you mean '{}' instead of '{}'?",
+                    message, keyword, typo
+                );
+                return error(self.peek(), code, &message);
+            }
         }
-    }
-
-    fn parse_error(&self, message: &str) -> String {
-        let token = self.peek();
-        error(token, message)
+        error(self.peek(), code, message)
     }
 
     fn matches(&mut self, token_types: &[TokenType]) -> bool {
@@ -540,6 +734,10 @@ impl<'a> Parser<'a> {
         if !self.is_at_end() {
             self.current += 1;
         }
+        // Like `synchronize`, a tolerant parse is back in step after a ';'.
+        if self.previous().typ == TokenType::SemiColon {
+            self.panicking = false;
+        }
         self.previous()
     }
 
@@ -555,3 +753,100 @@ impl<'a> Parser<'a> {
         self.tokens[self.current - 1].clone()
     }
 }
+
+fn error(token: &Token, code: &'static str, message: &str) -> Diagnostic {
+    Diagnostic::error(Phase::Parse, code, token, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::literal::Literal;
+    use crate::lexer::scanner::Scanner;
+
+    // The statements and the codes and offsets of the errors.
+    fn parse(source: &str, tolerant: bool) -> (Declarations, Vec<(&'static str, usize)>) {
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens, false);
+        parser.tolerant = tolerant;
+        parser.parse();
+        let errors = parser
+            .errors
+            .iter()
+            .map(|e| (e.code, e.span.offset))
+            .collect();
+        (parser.statements, errors)
+    }
+
+    #[test]
+    fn places_error_nodes() {
+        let (statements, errors) = parse("print 1 +;\n) 2;", true);
+        let expected = vec![
+            (code::EXPECTED_EXPRESSION, 9),
+            (code::EXPECTED_EXPRESSION, 11),
+        ];
+        assert_eq!(errors, expected);
+        assert_eq!(statements.len(), 3);
+        match &statements[0] {
+            Stmt::Print(expr) => match &**expr {
+                Expr::Binary(left, _, right) => {
+                    assert!(matches!(**left, Expr::Literal(Literal::Number(_), _)));
+                    assert!(matches!(**right, Expr::Error(span) if span.offset == 9));
+                }
+                other => panic!("expected a binary expression, got {:?}", other),
+            },
+            other => panic!("expected a print statement, got {:?}", other),
+        }
+        assert!(matches!(statements[1], Stmt::Error(span) if span.offset == 11));
+        assert!(matches!(statements[2], Stmt::Expression(_)));
+    }
+
+    #[test]
+    fn inserts_missing_tokens() {
+        let (statements, errors) = parse("var = 2;", true);
+        assert_eq!(errors, vec![(code::EXPECTED_TOKEN, 4)]);
+        match &statements[..] {
+            [Stmt::Var(name, value)] => {
+                assert!(name.is_missing());
+                assert_eq!((name.typ, name.offset), (TokenType::Identifier, 4));
+                assert!(matches!(**value, Expr::Literal(Literal::Number(_), _)));
+            }
+            other => panic!("expected a variable declaration, got {:?}", other),
+        }
+        // The missing ')' is inserted, so the body is still parsed.
+        let (statements, errors) = parse("fun f(a { return a; }", true);
+        assert_eq!(errors, vec![(code::EXPECTED_TOKEN, 8)]);
+        match &statements[..] {
+            [Stmt::Function(name, params, body)] => {
+                assert_eq!(name.lexeme, "f");
+                assert_eq!(params.len(), 1);
+                assert!(matches!(body[..], [Stmt::Return(..)]));
+            }
+            other => panic!("expected a function, got {:?}", other),
+        }
+        let (statements, errors) = parse("{ print 1;", true);
+        assert_eq!(errors, vec![(code::UNCLOSED_BLOCK, 0)]);
+        assert!(matches!(&statements[..], [Stmt::Block(body)] if body.len() == 1));
+    }
+
+    #[test]
+    fn reports_only_the_first_error_until_back_in_step() {
+        let (_, errors) = parse("print (1 + ;\nprint 2;\nprint );", true);
+        assert_eq!(
+            errors,
+            vec![
+                (code::EXPECTED_EXPRESSION, 11),
+                (code::EXPECTED_EXPRESSION, 28)
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_parsing_drops_broken_statements() {
+        let (statements, errors) = parse("print 1 +;\nvar = 2;\nprint 3;", false);
+        let expected = vec![(code::EXPECTED_EXPRESSION, 9), (code::EXPECTED_TOKEN, 15)];
+        assert_eq!(errors, expected);
+        assert!(matches!(&statements[..], [Stmt::Print(_)]));
+    }
+}
@@ -1,25 +1,47 @@
-use crate::error::report::error;
+use crate::error::code;
+use crate::error::diagnostic::{Diagnostic, Phase};
 use crate::interpreter::interpreter::Interpreter;
 use crate::lexer::literal::Literal;
-use crate::lexer::token::Token;
+use crate::lexer::token::{Span, Token};
 use crate::parser::expression::Expr;
 use crate::parser::statement::{Declarations, Stmt};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub struct Resolver<'a> {
     interpreter: &'a mut Interpreter,
-    scopes: Vec<HashMap<String, bool>>,
+    scopes: Vec<HashMap<String, Local>>,
+    // Globals declared so far in this program, for shadowing warnings. Natives
+    // and globals from earlier REPL lines are not included.
+    globals: HashSet<String>,
     current_function: FunctionType,
     current_class: ClassType,
+    errors: Vec<Diagnostic>,
+    pub warnings: Vec<Diagnostic>,
 }
 
-type ResolverError = Result<(), String>;
+// A name declared in a local scope. `this` and `super` have no declaration
+// and are never reported as unused.
+struct Local {
+    declaration: Option<Token>,
+    binding: Binding,
+    defined: bool,
+    read: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Binding {
+    Variable,
+    Parameter,
+    Function,
+    Class,
+}
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 enum FunctionType {
     Function,
     Method,
     Initializer,
+    Hook,
     NotAFunction,
 }
 
@@ -31,26 +53,39 @@ enum ClassType {
 }
 
 impl<'a> Resolver<'a> {
-    pub fn new(interpreter: &mut Interpreter) -> Resolver {
+    pub fn new(interpreter: &mut Interpreter) -> Resolver<'_> {
         let scopes = Vec::new();
         let current_function = FunctionType::NotAFunction;
         let current_class = ClassType::NotAClass;
         Resolver {
             interpreter,
             scopes,
+            globals: HashSet::new(),
             current_function,
             current_class,
+            errors: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
-    pub fn resolve(&mut self, statements: &Declarations) -> ResolverError {
-        for statement in statements {
-            self.visit_stmt(statement)?;
+    // Reports every error in `statements`, not just the first. Warnings are
+    // left in `warnings`, in source order.
+    pub fn resolve(&mut self, statements: &Declarations) -> Result<(), Vec<Diagnostic>> {
+        self.resolve_statements(statements);
+        self.warnings.sort_by_key(|warning| warning.span.offset);
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
         }
         Ok(())
     }
 
-    fn visit_stmt(&mut self, statement: &Stmt) -> ResolverError {
+    fn resolve_statements(&mut self, statements: &Declarations) {
+        for statement in statements {
+            self.visit_stmt(statement);
+        }
+    }
+
+    fn visit_stmt(&mut self, statement: &Stmt) {
         match statement {
             Stmt::Block(body) => self.visit_block_stmt(body),
             Stmt::Var(name, initializer) => self.visit_var_stmt(name, initializer),
@@ -63,82 +98,84 @@ impl<'a> Resolver<'a> {
             Stmt::While(condition, body) => self.visit_while_stmt(condition, body),
             Stmt::Function(name, params, body) => self.visit_function_stmt(name, params, body),
             Stmt::Getter(name, body) => self.visit_getter_stmt(name, body),
-            Stmt::Class(name, methods, super_class) => {
-                self.visit_class_stmt(name, methods, super_class)
+            Stmt::Class(name, methods, statics, super_class) => {
+                self.visit_class_stmt(name, methods, statics, super_class)
+            }
+            Stmt::Record(name, fields, methods, statics) => {
+                self.visit_record_stmt(name, fields, methods, statics)
             }
+            Stmt::Error(_) => (),
         }
     }
 
-    fn visit_block_stmt(&mut self, statements: &Declarations) -> ResolverError {
+    fn visit_block_stmt(&mut self, statements: &Declarations) {
         self.begin_scope();
-        self.resolve(statements)?;
+        self.resolve_statements(statements);
         self.end_scope();
-        Ok(())
     }
 
-    fn visit_var_stmt(&mut self, name: &Token, initializer: &Expr) -> ResolverError {
-        self.declare(name)?;
+    fn visit_var_stmt(&mut self, name: &Token, initializer: &Expr) {
+        self.declare(name, Binding::Variable);
         match initializer {
-            Expr::Literal(Literal::Nothing) => Ok(()),
+            Expr::Literal(Literal::Nothing, _) => (),
             _ => self.visit_expr(initializer),
-        }?;
+        }
         self.define(name);
-        Ok(())
     }
 
-    fn visit_expression_stmt(&mut self, expr: &Expr) -> ResolverError {
+    fn visit_expression_stmt(&mut self, expr: &Expr) {
         self.visit_expr(expr)
     }
 
-    fn visit_if_stmt(
-        &mut self,
-        condition: &Expr,
-        then_branch: &Stmt,
-        else_branch: &Option<Stmt>,
-    ) -> ResolverError {
-        self.visit_expr(condition)?;
-        self.visit_stmt(then_branch)?;
+    fn visit_if_stmt(&mut self, condition: &Expr, then_branch: &Stmt, else_branch: &Option<Stmt>) {
+        self.constant_condition(condition, false);
+        self.visit_expr(condition);
+        self.visit_stmt(then_branch);
         if let Some(statement) = else_branch {
-            self.visit_stmt(statement)?;
+            self.visit_stmt(statement);
         }
-        Ok(())
     }
 
-    fn visit_print_stmt(&mut self, expr: &Expr) -> ResolverError {
+    fn visit_print_stmt(&mut self, expr: &Expr) {
         self.visit_expr(expr)
     }
 
-    fn visit_return_stmt(&mut self, keyword: &Token, value: &Expr) -> ResolverError {
+    fn visit_return_stmt(&mut self, keyword: &Token, value: &Expr) {
         if self.current_function == FunctionType::NotAFunction {
-            return Err(error(keyword, "Cannot return from top-level code."));
+            self.error(
+                keyword,
+                code::TOP_LEVEL_RETURN,
+                "Cannot return from top-level code.",
+            );
         }
-        if self.current_function == FunctionType::Initializer {
-            return Err(error(keyword, "Cannot return a value from an initializer."));
+        let has_value = !matches!(value, Expr::Literal(Literal::Nothing, _));
+        if self.current_function == FunctionType::Initializer && has_value {
+            self.error(
+                keyword,
+                code::INITIALIZER_RETURN,
+                "Cannot return a value from an initializer.",
+            );
         }
         match value {
-            Expr::Literal(Literal::Nothing) => Ok(()),
+            Expr::Literal(Literal::Nothing, _) => (),
             _ => self.visit_expr(value),
         }
     }
 
-    fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) -> ResolverError {
-        self.visit_expr(condition)?;
+    fn visit_while_stmt(&mut self, condition: &Expr, body: &Stmt) {
+        self.constant_condition(condition, true);
+        self.visit_expr(condition);
         self.visit_stmt(body)
     }
 
-    fn visit_function_stmt(
-        &mut self,
-        name: &Token,
-        params: &Vec<Token>,
-        body: &Declarations,
-    ) -> ResolverError {
-        self.declare(name)?;
+    fn visit_function_stmt(&mut self, name: &Token, params: &Vec<Token>, body: &Declarations) {
+        self.declare(name, Binding::Function);
         self.define(name);
         self.resolve_function(params, body, FunctionType::Function)
     }
 
-    fn visit_getter_stmt(&mut self, name: &Token, body: &Declarations) -> ResolverError {
-        self.declare(name)?;
+    fn visit_getter_stmt(&mut self, name: &Token, body: &Declarations) {
+        self.declare(name, Binding::Function);
         self.define(name);
         self.resolve_function(&Vec::new(), body, FunctionType::Method)
     }
@@ -147,179 +184,297 @@ impl<'a> Resolver<'a> {
         &mut self,
         name: &Token,
         methods: &Vec<Stmt>,
+        statics: &Vec<Stmt>,
         super_class: &Option<Expr>,
-    ) -> ResolverError {
+    ) {
         let enclosing_class = self.current_class;
         self.current_class = ClassType::Class;
-        self.declare(name)?;
+        self.declare(name, Binding::Class);
         self.define(name);
         if let Some(Expr::Variable(super_class_name)) = super_class {
-            if super_class_name.lexeme == name.lexeme {
-                return Err(error(name, "A class can't inherit from itself."));
+            if super_class_name.lexeme == name.lexeme && !name.is_missing() {
+                self.error(
+                    name,
+                    code::SELF_INHERITANCE,
+                    "A class can't inherit from itself.",
+                );
             }
         }
         if let Some(class) = super_class {
             self.current_class = ClassType::SubClass;
-            self.visit_expr(class)?;
+            self.visit_expr(class);
         }
-        if let Some(_) = super_class {
+        if super_class.is_some() {
             self.begin_scope();
-            if let Some(scope) = self.scopes.last_mut() {
-                scope.insert("super".to_string(), true);
-            }
+            self.implicit("super");
         }
         self.begin_scope();
-        if let Some(scope) = self.scopes.last_mut() {
-            scope.insert("this".to_string(), true);
-        }
+        self.implicit("this");
         for method in methods {
+            if let Stmt::Function(method_name, params, body) = method {
+                self.resolve_function(params, body, method_type(method_name));
+            }
+        }
+        for method in statics {
             if let Stmt::Function(_, params, body) = method {
-                let declaration = if name.lexeme == "init" {
-                    FunctionType::Initializer
-                } else {
-                    FunctionType::Method
-                };
-                self.resolve_function(params, body, declaration)?;
+                self.resolve_function(params, body, FunctionType::Method);
             }
         }
         self.end_scope();
-        if let Some(_) = super_class {
+        if super_class.is_some() {
             self.end_scope();
         }
         self.current_class = enclosing_class;
-        Ok(())
     }
 
-    fn visit_expr(&mut self, expr: &Expr) -> ResolverError {
+    fn visit_record_stmt(
+        &mut self,
+        name: &Token,
+        fields: &[Token],
+        methods: &Vec<Stmt>,
+        statics: &Vec<Stmt>,
+    ) {
+        let enclosing_class = self.current_class;
+        self.current_class = ClassType::Class;
+        self.declare(name, Binding::Class);
+        self.define(name);
+        for (i, field) in fields.iter().enumerate() {
+            let duplicate = fields[..i].iter().any(|other| other.lexeme == field.lexeme);
+            if duplicate && !field.is_missing() {
+                self.error(
+                    field,
+                    code::DUPLICATE_FIELD,
+                    "Duplicate field name in record.",
+                );
+            }
+        }
+        self.begin_scope();
+        self.implicit("this");
+        for method in methods {
+            match method {
+                Stmt::Function(method_name, params, body) => {
+                    if method_name.lexeme == "init" {
+                        self.error(
+                            method_name,
+                            code::RECORD_INITIALIZER,
+                            "Records cannot declare an initializer.",
+                        );
+                    }
+                    self.resolve_function(params, body, method_type(method_name))
+                }
+                Stmt::Getter(_, body) => {
+                    self.resolve_function(&Vec::new(), body, FunctionType::Method)
+                }
+                _ => (),
+            }
+        }
+        for method in statics {
+            if let Stmt::Function(_, params, body) = method {
+                self.resolve_function(params, body, FunctionType::Method);
+            }
+        }
+        self.end_scope();
+        self.current_class = enclosing_class;
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
         match expr {
             Expr::Variable(var) => self.visit_variable_expr(var),
             Expr::Assign(name, value) => self.visit_assign_expr(name, value),
             Expr::Binary(left, _, right) => self.visit_binary_expr(left, right),
             Expr::Logical(left, _, right) => self.visit_logical_expr(left, right),
             Expr::Ternary(left, middle, right) => self.visit_ternary_expr(left, middle, right),
-            Expr::Grouping(group) => self.visit_grouping_expr(group),
+            Expr::Grouping(group, _) => self.visit_grouping_expr(group),
             Expr::Unary(_, right) => self.visit_unary_expr(right),
             Expr::Call(callee, _, arguments) => self.visit_call_expr(callee, arguments),
-            Expr::Lambda(args, body) => self.visit_lambda_expr(args, body),
+            Expr::Lambda(args, body, _) => self.visit_lambda_expr(args, body),
             Expr::Get(object, _) => self.visit_get_expr(object),
-            Expr::Set(object, _, value) => self.visit_set_expr(object, value),
+            Expr::Set(object, name, value) => self.visit_set_expr(object, name, value),
             Expr::This(name) => self.visit_this_expr(name),
             Expr::Super(keyword, _) => self.visit_super_expr(keyword),
-            Expr::Literal(_) => self.visit_literal(),
+            Expr::Literal(..) => self.visit_literal(),
+            Expr::Error(_) => (),
         }
     }
 
-    fn visit_variable_expr(&mut self, name: &Token) -> ResolverError {
+    fn visit_variable_expr(&mut self, name: &Token) {
         if let Some(scope) = self.scopes.last() {
-            if let Some(false) = scope.get(&name.lexeme) {
-                return Err(error(
+            if let Some(Local { defined: false, .. }) = scope.get(&name.lexeme) {
+                self.error(
                     name,
+                    code::SELF_INITIALIZER,
                     "Cannot read local variable in its own initializer.",
-                ));
+                );
+                return;
+            }
+        }
+        if let Some(i) = self.resolve_local(name) {
+            if let Some(local) = self.scopes[i].get_mut(&name.lexeme) {
+                local.read = true;
             }
         }
-        self.resolve_local(name);
-        Ok(())
     }
 
-    fn visit_assign_expr(&mut self, name: &Token, value: &Expr) -> ResolverError {
-        self.visit_expr(value)?;
+    fn visit_assign_expr(&mut self, name: &Token, value: &Expr) {
+        if let Expr::Variable(source) = value {
+            if source.lexeme == name.lexeme {
+                let message = format!("Variable '{}' is assigned to itself.", name.lexeme);
+                self.warning(
+                    name.span().to(&value.span()),
+                    code::SELF_ASSIGNMENT,
+                    &message,
+                );
+            }
+        }
+        self.visit_expr(value);
         self.resolve_local(name);
-        Ok(())
     }
 
-    fn visit_binary_expr(&mut self, left: &Expr, right: &Expr) -> ResolverError {
-        self.visit_expr(left)?;
+    fn visit_binary_expr(&mut self, left: &Expr, right: &Expr) {
+        self.visit_expr(left);
         self.visit_expr(right)
     }
 
-    fn visit_logical_expr(&mut self, left: &Expr, right: &Expr) -> ResolverError {
-        self.visit_expr(left)?;
+    fn visit_logical_expr(&mut self, left: &Expr, right: &Expr) {
+        self.visit_expr(left);
         self.visit_expr(right)
     }
 
-    fn visit_ternary_expr(&mut self, left: &Expr, middle: &Expr, right: &Expr) -> ResolverError {
-        self.visit_expr(left)?;
-        self.visit_expr(middle)?;
+    fn visit_ternary_expr(&mut self, left: &Expr, middle: &Expr, right: &Expr) {
+        self.constant_condition(left, false);
+        self.visit_expr(left);
+        self.visit_expr(middle);
         self.visit_expr(right)
     }
 
-    fn visit_grouping_expr(&mut self, group: &Expr) -> ResolverError {
-        self.visit_expr(&group)
+    fn visit_grouping_expr(&mut self, group: &Expr) {
+        self.visit_expr(group)
     }
 
-    fn visit_unary_expr(&mut self, expr: &Expr) -> ResolverError {
+    fn visit_unary_expr(&mut self, expr: &Expr) {
         self.visit_expr(expr)
     }
 
-    fn visit_call_expr(&mut self, callee: &Expr, arguments: &Vec<Box<Expr>>) -> ResolverError {
-        self.visit_expr(callee)?;
+    fn visit_call_expr(&mut self, callee: &Expr, arguments: &[Expr]) {
+        self.visit_expr(callee);
         for argument in arguments {
-            self.visit_expr(argument)?;
+            self.visit_expr(argument);
         }
-        Ok(())
     }
 
-    fn visit_lambda_expr(&mut self, params: &Vec<Token>, body: &Declarations) -> ResolverError {
-        self.begin_scope();
-        for param in params {
-            self.declare(param)?;
-            self.define(param);
-        }
-        self.resolve(body)?;
-        self.end_scope();
-        Ok(())
+    fn visit_lambda_expr(&mut self, params: &Vec<Token>, body: &Declarations) {
+        self.resolve_function(params, body, FunctionType::Function)
     }
 
-    fn visit_get_expr(&mut self, object: &Expr) -> ResolverError {
+    fn visit_get_expr(&mut self, object: &Expr) {
         self.visit_expr(object)
     }
 
-    fn visit_set_expr(&mut self, object: &Expr, value: &Expr) -> ResolverError {
-        self.visit_expr(value)?;
-        self.visit_expr(object)?;
-        Ok(())
+    fn visit_set_expr(&mut self, object: &Expr, name: &Token, value: &Expr) {
+        if let (Expr::This(_), Expr::Get(source, property)) = (object, value) {
+            if matches!(**source, Expr::This(_)) && property.lexeme == name.lexeme {
+                let message = format!("Property '{}' is assigned to itself.", name.lexeme);
+                self.warning(
+                    object.span().to(&value.span()),
+                    code::SELF_ASSIGNMENT,
+                    &message,
+                );
+            }
+        }
+        self.visit_expr(value);
+        self.visit_expr(object);
     }
 
-    fn visit_this_expr(&mut self, name: &Token) -> ResolverError {
+    fn visit_this_expr(&mut self, name: &Token) {
         if self.current_class == ClassType::NotAClass {
-            return Err(error(name, "Cannot use 'this' outside of a class."));
+            self.error(
+                name,
+                code::THIS_OUTSIDE_CLASS,
+                "Cannot use 'this' outside of a class.",
+            );
+            return;
         }
         self.resolve_local(name);
-        Ok(())
     }
 
-    fn visit_super_expr(&mut self, keyword: &Token) -> ResolverError {
-        return match self.current_class {
-            ClassType::Class => Err(error(keyword, "Can't use 'super' in a class with no superclass.")),
-            ClassType::NotAClass => Err(error(keyword, "Can't use 'super' outside of a class.")),
+    fn visit_super_expr(&mut self, keyword: &Token) {
+        match self.current_class {
+            ClassType::Class => self.error(
+                keyword,
+                code::SUPER_WITHOUT_SUPERCLASS,
+                "Can't use 'super' in a class with no superclass.",
+            ),
+            ClassType::NotAClass => self.error(
+                keyword,
+                code::SUPER_OUTSIDE_CLASS,
+                "Can't use 'super' outside of a class.",
+            ),
             ClassType::SubClass => {
                 self.resolve_local(keyword);
-                Ok(())
             }
         }
     }
 
-    fn visit_literal(&self) -> ResolverError {
-        Ok(())
+    fn visit_literal(&self) {}
+
+    // A second declaration is reported and otherwise ignored, so the scope
+    // keeps the first one.
+    fn declare(&mut self, name: &Token, binding: Binding) {
+        // Names inserted by tolerant parsing cannot clash or go unused.
+        if name.is_missing() {
+            return;
+        }
+        let scope = match self.scopes.last() {
+            Some(scope) => scope,
+            None => {
+                self.globals.insert(name.lexeme.clone());
+                return;
+            }
+        };
+        if scope.contains_key(&name.lexeme) {
+            self.error(
+                name,
+                code::DUPLICATE_VARIABLE,
+                "Variable with this name already declared in this scope.",
+            );
+            return;
+        }
+        let outer = &self.scopes[..self.scopes_depth()];
+        if outer.iter().any(|scope| scope.contains_key(&name.lexeme))
+            || self.globals.contains(&name.lexeme)
+        {
+            let message = format!("'{}' shadows a variable in an outer scope.", name.lexeme);
+            self.warning(name.span(), code::SHADOWED_VARIABLE, &message);
+        }
+        let local = Local {
+            declaration: Some(name.clone()),
+            binding,
+            defined: false,
+            read: false,
+        };
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), local);
+        }
     }
 
-    fn declare(&mut self, name: &Token) -> ResolverError {
+    fn define(&mut self, name: &Token) {
         if let Some(scope) = self.scopes.last_mut() {
-            if scope.contains_key(&name.lexeme) {
-                return Err(error(
-                    name,
-                    "Variable with this name already declared in this scope.",
-                ));
+            if let Some(local) = scope.get_mut(&name.lexeme) {
+                local.defined = true;
             }
-            scope.insert(name.lexeme.clone(), false);
         }
-        Ok(())
     }
 
-    fn define(&mut self, name: &Token) {
+    // Binds `this` or `super` in the innermost scope.
+    fn implicit(&mut self, name: &str) {
+        let local = Local {
+            declaration: None,
+            binding: Binding::Variable,
+            defined: true,
+            read: true,
+        };
         if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name.lexeme.clone(), true);
+            scope.insert(name.to_string(), local);
         }
     }
 
@@ -327,40 +482,222 @@ impl<'a> Resolver<'a> {
         self.scopes.push(HashMap::new());
     }
 
+    // Warns about the locals of the scope that were never read. Names
+    // starting with an underscore are meant to be unused.
     fn end_scope(&mut self) {
-        self.scopes.pop();
+        let scope = match self.scopes.pop() {
+            Some(scope) => scope,
+            None => return,
+        };
+        for local in scope.values().filter(|local| !local.read) {
+            let name = match &local.declaration {
+                Some(name) if !name.lexeme.starts_with('_') => name,
+                _ => continue,
+            };
+            let (code, what) = match local.binding {
+                Binding::Variable => (code::UNUSED_VARIABLE, "Local variable"),
+                Binding::Parameter => (code::UNUSED_PARAMETER, "Parameter"),
+                Binding::Function => (code::UNUSED_VARIABLE, "Local function"),
+                Binding::Class => (code::UNUSED_VARIABLE, "Local class"),
+            };
+            let message = format!("{} '{}' is never used.", what, name.lexeme);
+            let note = format!(
+                "Rename it to '_{}' if it is meant to be unused.",
+                name.lexeme
+            );
+            let warning = Diagnostic::warning(Phase::Resolve, code, name.span(), &message);
+            self.warnings.push(warning.note(&note));
+        }
     }
 
-    fn resolve_local(&mut self, name: &Token) {
-        if self.scopes.len() > 0 {
+    // The index of the scope that declares `name`, if it is a local.
+    fn resolve_local(&mut self, name: &Token) -> Option<usize> {
+        if !self.scopes.is_empty() {
             for i in (0..=self.scopes_depth()).rev() {
                 if self.scopes[i].contains_key(&name.lexeme) {
-                    self.interpreter.resolve(name, self.scopes_depth() - i)
+                    self.interpreter.resolve(name, self.scopes_depth() - i);
+                    return Some(i);
                 }
             }
         }
+        None
     }
 
-    fn resolve_function(
-        &mut self,
-        params: &Vec<Token>,
-        body: &Declarations,
-        typ: FunctionType,
-    ) -> ResolverError {
+    // `while (true)` is the usual way to loop until a return, and `for (;;)`
+    // becomes one, so loops may have a condition that is always true.
+    fn constant_condition(&mut self, condition: &Expr, is_loop: bool) {
+        let mut expr = condition;
+        while let Expr::Grouping(inner, _) = expr {
+            expr = inner;
+        }
+        if let Expr::Literal(value, _) = expr {
+            let truthy = !matches!(value, Literal::Nothing | Literal::Bool(false));
+            if truthy && is_loop {
+                return;
+            }
+            let message = format!("Condition is always {}.", truthy);
+            self.warning(condition.span(), code::CONSTANT_CONDITION, &message);
+        }
+    }
+
+    fn resolve_function(&mut self, params: &Vec<Token>, body: &Declarations, typ: FunctionType) {
         let enclosing_function = self.current_function;
         self.current_function = typ;
         self.begin_scope();
         for param in params {
-            self.declare(param)?;
+            self.declare(param, Binding::Parameter);
             self.define(param);
+            // A hook is called with arguments it may have no use for.
+            if typ == FunctionType::Hook {
+                let scope = self.scopes.last_mut();
+                if let Some(local) = scope.and_then(|scope| scope.get_mut(&param.lexeme)) {
+                    local.read = true;
+                }
+            }
         }
-        self.resolve(body)?;
+        self.resolve_statements(body);
         self.end_scope();
         self.current_function = enclosing_function;
-        Ok(())
     }
 
     fn scopes_depth(&self) -> usize {
         self.scopes.len() - 1
     }
+
+    fn warning(&mut self, span: Span, code: &'static str, message: &str) {
+        self.warnings
+            .push(Diagnostic::warning(Phase::Resolve, code, span, message));
+    }
+
+    fn error(&mut self, token: &Token, code: &'static str, message: &str) {
+        self.errors
+            .push(Diagnostic::error(Phase::Resolve, code, token, message));
+    }
+}
+
+// Methods the interpreter calls on its own, and whose parameters are therefore
+// fixed, are hooks.
+fn method_type(name: &Token) -> FunctionType {
+    match name.lexeme.as_str() {
+        "init" => FunctionType::Initializer,
+        "call" | "__get" | "__set" | "__invoke" => FunctionType::Hook,
+        _ => FunctionType::Method,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::scanner::Scanner;
+    use crate::parser::parser::Parser;
+
+    // The codes and offsets of the warnings for `source`, parser's first.
+    fn warnings(source: &str) -> Vec<(&'static str, usize)> {
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens, false);
+        parser.parse();
+        assert!(parser.errors.is_empty());
+        let mut interpreter = Interpreter::new();
+        let mut resolver = Resolver::new(&mut interpreter);
+        resolver.resolve(&parser.statements).unwrap();
+        let all = parser.warnings.iter().chain(resolver.warnings.iter());
+        all.map(|warning| (warning.code, warning.span.offset))
+            .collect()
+    }
+
+    type Lines = Vec<(&'static str, u32)>;
+
+    // The codes and lines of the errors and of the warnings for `source`.
+    fn diagnostics(source: &str) -> (Lines, Lines) {
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens, false);
+        parser.parse();
+        assert!(parser.errors.is_empty());
+        let mut interpreter = Interpreter::new();
+        let mut resolver = Resolver::new(&mut interpreter);
+        let errors = resolver.resolve(&parser.statements).unwrap_err();
+        let lines = |all: &[Diagnostic]| all.iter().map(|d| (d.code, d.span.line)).collect();
+        (lines(&errors), lines(&resolver.warnings))
+    }
+
+    #[test]
+    fn reports_every_error_in_order() {
+        let source = "{ var a = a; print a; }
+            class A { m() { print this; } }
+            { var b = 1; var b = 2; print b; }
+            return 1;
+            print this;
+            fun f() { { var c = 1; { var c = 2; print c; } } return; }
+            { var d = 1; var d = 2; print d; }";
+        let (errors, warnings) = diagnostics(source);
+        let expected = vec![
+            (code::SELF_INITIALIZER, 1),
+            (code::DUPLICATE_VARIABLE, 3),
+            (code::TOP_LEVEL_RETURN, 4),
+            (code::THIS_OUTSIDE_CLASS, 5),
+            (code::DUPLICATE_VARIABLE, 7),
+        ];
+        assert_eq!(errors, expected);
+        // Each error leaves the resolver in step: the method after the first
+        // one may use `this`, the return in `f` is allowed after the
+        // top-level one, and both `c`s are found in their own scopes.
+        let expected = vec![(code::UNUSED_VARIABLE, 6), (code::SHADOWED_VARIABLE, 6)];
+        assert_eq!(warnings, expected);
+    }
+
+    #[test]
+    fn resolves_lambdas_as_functions() {
+        // A return in a lambda leaves the lambda, so it is allowed at the top
+        // level and may return a value inside an initializer.
+        let source = "fun apply(g) { return g(); }
+            print apply(fun () { return 1; });
+            class A { init() { apply(fun () { return 2; }); } }";
+        assert!(warnings(source).is_empty());
+    }
+
+    #[test]
+    fn reports_shadowed_globals() {
+        let source = "var a = 1; fun f(a) { print a; } { var a = 2; print a; }";
+        let expected = vec![(code::SHADOWED_VARIABLE, 17), (code::SHADOWED_VARIABLE, 39)];
+        assert_eq!(warnings(source), expected);
+        // Only globals declared before the scope are known.
+        assert!(warnings("fun f() { var b = 1; print b; } var b = 2;").is_empty());
+    }
+
+    #[test]
+    fn points_unreachable_code_at_its_first_token() {
+        let source = "fun f() { return; print 1; print 2; }";
+        assert_eq!(warnings(source), vec![(code::UNREACHABLE_CODE, 18)]);
+    }
+
+    #[test]
+    fn does_not_report_unused_hook_parameters() {
+        let source = "class A {
+                __get(name) { return 1; }
+                __set(name, value) {}
+                __invoke(name, args) { return 2; }
+                call(a) {}
+                m(b) {}
+            }
+            record R(x) { call(c) {} }";
+        let expected = vec![(code::UNUSED_PARAMETER, 186)];
+        assert_eq!(warnings(source), expected);
+    }
+
+    #[test]
+    fn suggests_an_underscore_for_unused_names() {
+        let source = "fun f(a) {}";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens, false);
+        parser.parse();
+        let mut interpreter = Interpreter::new();
+        let mut resolver = Resolver::new(&mut interpreter);
+        resolver.resolve(&parser.statements).unwrap();
+        let note = "Rename it to '_a' if it is meant to be unused.";
+        assert!(resolver.warnings[0].render(source).contains(note));
+        assert!(warnings("fun f(_a) {}").is_empty());
+    }
 }
@@ -1,4 +1,4 @@
-use crate::lexer::token::Token;
+use crate::lexer::token::{Span, Token};
 use crate::parser::expression::{Expr, Expression};
 
 #[derive(Debug, PartialEq, Clone)]
@@ -12,7 +12,10 @@ pub enum Stmt {
     Function(Token, Vec<Token>, Declarations),
     Getter(Token, Declarations),
     Return(Token, Expression),
-    Class(Token, Vec<Stmt>, Box<Option<Expr>>),
+    Class(Token, Vec<Stmt>, Vec<Stmt>, Box<Option<Expr>>),
+    Record(Token, Vec<Token>, Vec<Stmt>, Vec<Stmt>),
+    // Tokens that tolerant parsing could make nothing of.
+    Error(Span),
 }
 
 pub type Declarations = Vec<Stmt>;
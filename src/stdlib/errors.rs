@@ -0,0 +1,68 @@
+use super::{define, expect_str, module};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::Arity;
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn load(globals: &mut Environment) {
+    define(globals, "try", Arity::Fixed(2), try_call);
+    define(globals, "throw", Arity::Fixed(1), throw);
+}
+
+// try(body, handler) calls body(). If that fails with a runtime error the
+// result is handler(error) instead, where the error object has the `kind`
+// of error (such as "TypeError" or "UserThrown"), `message`, `line` and a
+// `stack` of frames with `function` and `line`, innermost first.
+fn try_call(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let depth = interpreter.call_stack.len();
+    let err = match interpreter.call(args[0].clone(), paren, &Vec::new()) {
+        Ok(value) => return Ok(value),
+        Err(err) if err.kind() == ErrorKind::Exit => return Err(err),
+        Err(err) => err.with_stack(&interpreter.call_stack),
+    };
+    interpreter.call_stack.truncate(depth);
+    let error = error_object(&err);
+    interpreter.call(args[1].clone(), paren, &vec![error])
+}
+
+fn throw(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let message = expect_str(paren, "throw", &args[0])?;
+    Err(RuntimeError::new(
+        ErrorKind::UserThrown,
+        paren.clone(),
+        message,
+    ))
+}
+
+fn error_object(err: &RuntimeError) -> Literal {
+    let stack = err
+        .traceback()
+        .into_iter()
+        .map(|(function, line)| {
+            module(
+                "frame",
+                vec![
+                    ("function", Literal::Str(function)),
+                    ("line", Literal::Number(line as f64)),
+                ],
+            )
+        })
+        .collect();
+    module(
+        "error",
+        vec![
+            ("kind", Literal::Str(err.kind().to_string())),
+            ("message", Literal::Str(err.message().to_string())),
+            ("line", Literal::Number(err.line() as f64)),
+            ("stack", Literal::List(Rc::new(RefCell::new(stack)))),
+        ],
+    )
+}
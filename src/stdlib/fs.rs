@@ -0,0 +1,301 @@
+use super::{define, expect_str};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::Arity;
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FsAccess {
+    Denied,
+    All,
+    Within(Vec<PathBuf>),
+}
+
+impl FsAccess {
+    pub fn within(dirs: &[&str]) -> io::Result<FsAccess> {
+        let mut allowed = Vec::new();
+        for dir in dirs {
+            allowed.push(fs::canonicalize(dir)?);
+        }
+        Ok(FsAccess::Within(allowed))
+    }
+}
+
+pub fn load(globals: &mut Environment) {
+    define(globals, "readFile", Arity::Fixed(1), read_file);
+    define(globals, "writeFile", Arity::Fixed(2), write_file);
+    define(globals, "appendFile", Arity::Fixed(2), append_file);
+    define(globals, "readLines", Arity::Fixed(1), read_lines);
+    define(globals, "exists", Arity::Fixed(1), exists);
+    define(globals, "listDir", Arity::Fixed(1), list_dir);
+    define(globals, "mkdir", Arity::Fixed(1), mkdir);
+    define(globals, "remove", Arity::Fixed(1), remove);
+}
+
+fn read_file(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let path = checked_path(interpreter, paren, "readFile", &args[0])?;
+    let contents = fs::read_to_string(&path).map_err(|err| io_error(paren, "read", &path, err))?;
+    Ok(Literal::Str(contents))
+}
+
+fn write_file(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let path = checked_path(interpreter, paren, "writeFile", &args[0])?;
+    let contents = expect_str(paren, "writeFile", &args[1])?;
+    fs::write(&path, contents).map_err(|err| io_error(paren, "write", &path, err))?;
+    Ok(Literal::Nothing)
+}
+
+fn append_file(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let path = checked_path(interpreter, paren, "appendFile", &args[0])?;
+    let contents = expect_str(paren, "appendFile", &args[1])?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|err| io_error(paren, "append to", &path, err))?;
+    Ok(Literal::Nothing)
+}
+
+fn read_lines(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let path = checked_path(interpreter, paren, "readLines", &args[0])?;
+    let contents = fs::read_to_string(&path).map_err(|err| io_error(paren, "read", &path, err))?;
+    let lines = contents
+        .lines()
+        .map(|line| Literal::Str(line.to_string()))
+        .collect();
+    Ok(Literal::List(Rc::new(RefCell::new(lines))))
+}
+
+fn exists(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let path = checked_path(interpreter, paren, "exists", &args[0])?;
+    Ok(Literal::Bool(path.exists()))
+}
+
+fn list_dir(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let path = checked_path(interpreter, paren, "listDir", &args[0])?;
+    let mut names = Vec::new();
+    let entries = fs::read_dir(&path).map_err(|err| io_error(paren, "list", &path, err))?;
+    for entry in entries {
+        let entry = entry.map_err(|err| io_error(paren, "list", &path, err))?;
+        names.push(entry.file_name().to_string_lossy().into_owned());
+    }
+    names.sort();
+    let names = names.into_iter().map(Literal::Str).collect();
+    Ok(Literal::List(Rc::new(RefCell::new(names))))
+}
+
+fn mkdir(interpreter: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let path = checked_path(interpreter, paren, "mkdir", &args[0])?;
+    fs::create_dir_all(&path).map_err(|err| io_error(paren, "create", &path, err))?;
+    Ok(Literal::Nothing)
+}
+
+fn remove(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let path = checked_path(interpreter, paren, "remove", &args[0])?;
+    // A link to a directory is removed like a file, leaving the directory.
+    let is_dir = fs::symlink_metadata(&path).is_ok_and(|meta| meta.is_dir());
+    let result = if is_dir {
+        fs::remove_dir(&path)
+    } else {
+        fs::remove_file(&path)
+    };
+    result.map_err(|err| io_error(paren, "remove", &path, err))?;
+    Ok(Literal::Nothing)
+}
+
+fn checked_path(
+    interpreter: &Interpreter,
+    paren: &Token,
+    function: &str,
+    value: &Literal,
+) -> RuntimeResult<PathBuf> {
+    let path = PathBuf::from(expect_str(paren, function, value)?);
+    let allowed = match &interpreter.fs_access {
+        FsAccess::All => return Ok(path),
+        FsAccess::Within(allowed) => allowed,
+        FsAccess::Denied => {
+            return Err(RuntimeError::new(
+                ErrorKind::IoError,
+                paren.clone(),
+                &format!(
+                    "File system access is disabled; run with --allow-fs to use '{}'.",
+                    function
+                ),
+            ))
+        }
+    };
+    if !within(&path, allowed) {
+        return Err(RuntimeError::new(
+            ErrorKind::IoError,
+            paren.clone(),
+            &format!("Access to '{}' is not allowed.", path.display()),
+        ));
+    }
+    Ok(path)
+}
+
+// Whether the path, with its symlinks resolved, is inside one of the allowed
+// directories. Only the check uses the resolved path: the I/O is done on the
+// path as given, so that remove() and mkdir() act on a link and not on its
+// target. A link or directory swapped in between the check and the access,
+// for example in a part of the path that did not exist yet, is not caught.
+fn within(path: &Path, allowed: &[PathBuf]) -> bool {
+    match resolve(path) {
+        Some(resolved) => allowed.iter().any(|dir| resolved.starts_with(dir)),
+        None => false,
+    }
+}
+
+// Canonicalizes the longest existing prefix of the path, so that files which
+// do not exist yet can still be checked against the allowed directories. A
+// '..' in the part that does not exist yet has no file name and is rejected.
+// A symlink counts as existing even when its target does not, so a dangling
+// link fails to canonicalize and is rejected rather than followed later.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    while !existing.as_os_str().is_empty() && fs::symlink_metadata(existing).is_err() {
+        rest.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+    let mut resolved = if existing.as_os_str().is_empty() {
+        fs::canonicalize(".").ok()?
+    } else {
+        fs::canonicalize(existing).ok()?
+    };
+    for component in rest.iter().rev() {
+        resolved.push(component);
+    }
+    Some(resolved)
+}
+
+fn io_error(paren: &Token, action: &str, path: &Path, err: io::Error) -> RuntimeError {
+    RuntimeError::new(
+        ErrorKind::IoError,
+        paren.clone(),
+        &format!("Could not {} '{}': {}.", action, path.display(), err),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::token::{Span, TokenType};
+    use std::env;
+    use std::process;
+
+    // A fresh directory per test, with the canonical sandbox inside it.
+    fn scratch(name: &str) -> (PathBuf, Vec<PathBuf>) {
+        let root = env::temp_dir().join(format!("tlox-fs-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sandbox")).unwrap();
+        let root = fs::canonicalize(root).unwrap();
+        let allowed = vec![root.join("sandbox")];
+        (root, allowed)
+    }
+
+    #[test]
+    fn allows_new_files_inside() {
+        let (root, allowed) = scratch("new");
+        let sandbox = &allowed[0];
+        let file = sandbox.join("new.txt");
+        assert!(within(&file, &allowed));
+        assert!(!within(&root.join("out.txt"), &allowed));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn rejects_dot_dot_in_new_parts() {
+        let (root, allowed) = scratch("dotdot");
+        let sandbox = &allowed[0];
+        let path = sandbox.join("missing/../../out.txt");
+        assert!(!within(&path, &allowed));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_dangling_symlink_out_of_the_sandbox() {
+        let (root, allowed) = scratch("dangling");
+        let sandbox = &allowed[0];
+        let link = sandbox.join("link");
+        std::os::unix::fs::symlink(root.join("out").join("escaped"), &link).unwrap();
+        assert!(!within(&link, &allowed));
+        assert!(!within(&link.join("file"), &allowed));
+        assert!(!root.join("out").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolves_symlinks_to_their_target() {
+        let (root, allowed) = scratch("symlink");
+        let sandbox = &allowed[0];
+        fs::write(root.join("secret.txt"), "secret").unwrap();
+        fs::write(sandbox.join("data.txt"), "data").unwrap();
+        std::os::unix::fs::symlink(root.join("secret.txt"), sandbox.join("out")).unwrap();
+        std::os::unix::fs::symlink(sandbox.join("data.txt"), sandbox.join("in")).unwrap();
+        assert!(!within(&sandbox.join("out"), &allowed));
+        assert!(within(&sandbox.join("in"), &allowed));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn acts_on_symlinks_rather_than_their_targets() {
+        let (root, allowed) = scratch("act");
+        let sandbox = &allowed[0];
+        fs::create_dir(sandbox.join("dir")).unwrap();
+        fs::write(sandbox.join("dir/data.txt"), "data").unwrap();
+        std::os::unix::fs::symlink(sandbox.join("dir"), sandbox.join("link")).unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.fs_access = FsAccess::Within(allowed.clone());
+        let paren = Token::new(
+            TokenType::RightParen,
+            ")".to_string(),
+            Span::new(0, 1, 1, 1),
+            0,
+        );
+        let link = Literal::Str(sandbox.join("link").to_string_lossy().into_owned());
+        remove(&mut interpreter, &paren, &[link]).unwrap();
+        assert!(fs::symlink_metadata(sandbox.join("link")).is_err());
+        assert!(sandbox.join("dir/data.txt").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,44 @@
+use super::{define, map, regex, string};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::Arity;
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn load(globals: &mut Environment) {
+    define(globals, "clock", Arity::Fixed(0), clock);
+    define(globals, "id", Arity::Fixed(1), id);
+    define(globals, "same", Arity::Fixed(2), same);
+    define(globals, "Map", Arity::Fixed(0), map::new);
+    define(globals, "Regex", Arity::Fixed(1), regex::new);
+    define(
+        globals,
+        "fromCodePoint",
+        Arity::Variadic(1),
+        string::from_code_point,
+    );
+}
+
+fn clock(_: &mut Interpreter, _: &Token, _: &[Literal]) -> RuntimeResult<Literal> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(Literal::Number(now.as_secs_f64()))
+}
+
+fn id(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    match args[0].id() {
+        Some(id) => Ok(Literal::Number(id as f64)),
+        None => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            "Only instances, classes and functions have an identity.",
+        )),
+    }
+}
+
+fn same(_: &mut Interpreter, _: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    Ok(Literal::Bool(args[0] == args[1]))
+}
@@ -0,0 +1,62 @@
+use super::{define, expect_str};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::Arity;
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::io::{self, Read, Write};
+
+pub fn load(globals: &mut Environment) {
+    define(globals, "readLine", Arity::Fixed(0), read_line);
+    define(globals, "input", Arity::Fixed(1), input);
+    define(globals, "readAll", Arity::Fixed(0), read_all);
+}
+
+fn read_line(_: &mut Interpreter, paren: &Token, _: &[Literal]) -> RuntimeResult<Literal> {
+    next_line(paren)
+}
+
+fn input(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let prompt = expect_str(paren, "input", &args[0])?;
+    let mut stdout = io::stdout();
+    print!("{}", prompt);
+    stdout
+        .flush()
+        .map_err(|err| io_error(paren, "write to", "standard output", err))?;
+    next_line(paren)
+}
+
+fn read_all(_: &mut Interpreter, paren: &Token, _: &[Literal]) -> RuntimeResult<Literal> {
+    let mut contents = String::new();
+    io::stdin()
+        .read_to_string(&mut contents)
+        .map_err(|err| io_error(paren, "read from", "standard input", err))?;
+    Ok(Literal::Str(contents))
+}
+
+// Reads one line without its terminator, or nil once stdin is exhausted.
+fn next_line(paren: &Token) -> RuntimeResult<Literal> {
+    let mut line = String::new();
+    let read = io::stdin()
+        .read_line(&mut line)
+        .map_err(|err| io_error(paren, "read from", "standard input", err))?;
+    if read == 0 {
+        return Ok(Literal::Nothing);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    Ok(Literal::Str(line))
+}
+
+fn io_error(paren: &Token, action: &str, stream: &str, err: io::Error) -> RuntimeError {
+    RuntimeError::new(
+        ErrorKind::IoError,
+        paren.clone(),
+        &format!("Could not {} {}: {}.", action, stream, err),
+    )
+}
@@ -0,0 +1,570 @@
+use super::{expect_str, module, native};
+use crate::error::diagnostic::quote;
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::Arity;
+use crate::lexer::literal::{Instance, Literal};
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+// Deeper documents are rejected instead of overflowing the native stack.
+const MAX_DEPTH: usize = 512;
+
+pub fn load(globals: &mut Environment) {
+    let json = module(
+        "json",
+        vec![
+            ("parse", native("parse", Arity::Fixed(1), parse)),
+            (
+                "stringify",
+                native("stringify", Arity::Variadic(1), stringify),
+            ),
+        ],
+    );
+    globals.define("json".to_string(), json);
+}
+
+fn parse(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let text = expect_str(paren, "parse", &args[0])?;
+    let mut reader = Reader::new(text);
+    let value = reader.document().map_err(|(line, column, message)| {
+        RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            &format!(
+                "Invalid JSON at line {}, column {}: {}.",
+                line, column, message
+            ),
+        )
+    })?;
+    Ok(value)
+}
+
+fn stringify(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    if args.len() > 2 {
+        return Err(RuntimeError::new(
+            ErrorKind::ArityError,
+            paren.clone(),
+            "Wrong number of arguments.",
+        ));
+    }
+    let indent = match args.get(1) {
+        None | Some(Literal::Nothing) => None,
+        Some(Literal::Number(n)) if n.fract() == 0.0 && *n >= 0.0 && *n <= 10.0 => {
+            Some(" ".repeat(*n as usize))
+        }
+        Some(Literal::Str(s)) => Some(s.clone()),
+        Some(_) => {
+            return Err(RuntimeError::new(
+                ErrorKind::ValueError,
+                paren.clone(),
+                "Indent for 'stringify' must be a number between 0 and 10 or a string.",
+            ))
+        }
+    };
+    let mut writer = Writer {
+        indent: indent.filter(|indent| !indent.is_empty()),
+        out: String::new(),
+        path: Vec::new(),
+    };
+    writer
+        .value(&args[0], 0)
+        .map_err(|message| RuntimeError::new(ErrorKind::ValueError, paren.clone(), &message))?;
+    Ok(Literal::Str(writer.out))
+}
+
+type ParseResult<T> = Result<T, (usize, usize, String)>;
+
+struct Reader {
+    chars: Vec<char>,
+    current: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Reader {
+    fn new(text: &str) -> Reader {
+        Reader {
+            chars: text.chars().collect(),
+            current: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn document(&mut self) -> ParseResult<Literal> {
+        let value = self.value(0)?;
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(value),
+            Some(c) => Err(self.error(&format!("Unexpected character '{}'", c))),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> ParseResult<Literal> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("Nesting is too deep"));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => Ok(Literal::Str(self.string()?)),
+            Some('t') => self.keyword("true", Literal::Bool(true)),
+            Some('f') => self.keyword("false", Literal::Bool(false)),
+            Some('n') => self.keyword("null", Literal::Nothing),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(self.error(&format!("Unexpected character '{}'", c))),
+            None => Err(self.error("Unexpected end of input")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> ParseResult<Literal> {
+        self.advance();
+        let mut entries = BTreeMap::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.advance();
+            return Ok(Literal::Map(Rc::new(RefCell::new(entries))));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.expected("a string key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.consume(':', "':' after object key")?;
+            let value = self.value(depth + 1)?;
+            entries.insert(key, value);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.advance(),
+                Some('}') => {
+                    self.advance();
+                    return Ok(Literal::Map(Rc::new(RefCell::new(entries))));
+                }
+                _ => return Err(self.expected("',' or '}' in object")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> ParseResult<Literal> {
+        self.advance();
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.advance();
+            return Ok(Literal::List(Rc::new(RefCell::new(items))));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.advance(),
+                Some(']') => {
+                    self.advance();
+                    return Ok(Literal::List(Rc::new(RefCell::new(items))));
+                }
+                _ => return Err(self.expected("',' or ']' in array")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> ParseResult<String> {
+        self.advance();
+        let mut s = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') => {
+                    self.advance();
+                    return Ok(s);
+                }
+                Some('\\') => {
+                    self.advance();
+                    s.push(self.escape()?);
+                }
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error("Control character in string"))
+                }
+                Some(c) => {
+                    self.advance();
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> ParseResult<char> {
+        let c = match self.peek() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                self.advance();
+                return self.unicode_escape();
+            }
+            _ => return Err(self.error("Invalid escape sequence")),
+        };
+        self.advance();
+        Ok(c)
+    }
+
+    fn unicode_escape(&mut self) -> ParseResult<char> {
+        let (line, column) = (self.line, self.column);
+        let high = self.hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.peek() != Some('\\') || self.peek_next() != Some('u') {
+                return Err((line, column, "Unpaired surrogate in string".to_string()));
+            }
+            self.advance();
+            self.advance();
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err((line, column, "Unpaired surrogate in string".to_string()));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        std::char::from_u32(code)
+            .ok_or_else(|| (line, column, "Invalid unicode escape".to_string()))
+    }
+
+    fn hex4(&mut self) -> ParseResult<u32> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => {
+                    code = code * 16 + digit;
+                    self.advance();
+                }
+                None => return Err(self.expected("four hex digits in unicode escape")),
+            }
+        }
+        Ok(code)
+    }
+
+    fn number(&mut self) -> ParseResult<Literal> {
+        let (line, column) = (self.line, self.column);
+        let start = self.current;
+        if self.peek() == Some('-') {
+            self.advance();
+        }
+        match self.peek() {
+            Some('0') => self.advance(),
+            Some(c) if c.is_ascii_digit() => self.digits(),
+            _ => return Err(self.expected("a digit")),
+        }
+        if self.peek() == Some('.') {
+            self.advance();
+            if !self.peek_digit() {
+                return Err(self.expected("a digit after '.'"));
+            }
+            self.digits();
+        }
+        if let Some('e') | Some('E') = self.peek() {
+            self.advance();
+            if let Some('+') | Some('-') = self.peek() {
+                self.advance();
+            }
+            if !self.peek_digit() {
+                return Err(self.expected("a digit in exponent"));
+            }
+            self.digits();
+        }
+        let text: String = self.chars[start..self.current].iter().collect();
+        text.parse::<f64>()
+            .map(Literal::Number)
+            .map_err(|_| (line, column, format!("Invalid number '{}'", text)))
+    }
+
+    fn digits(&mut self) {
+        while self.peek_digit() {
+            self.advance();
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Literal) -> ParseResult<Literal> {
+        let (line, column) = (self.line, self.column);
+        for expected in word.chars() {
+            if self.peek() != Some(expected) {
+                return Err((line, column, "Invalid literal".to_string()));
+            }
+            self.advance();
+        }
+        Ok(value)
+    }
+
+    fn consume(&mut self, c: char, expected: &str) -> ParseResult<()> {
+        if self.peek() == Some(c) {
+            self.advance();
+            Ok(())
+        } else {
+            Err(self.expected(expected))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.advance();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn peek_digit(&self) -> bool {
+        matches!(self.peek(), Some(c) if c.is_ascii_digit())
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        self.chars.get(self.current + 1).copied()
+    }
+
+    fn advance(&mut self) {
+        if self.peek() == Some('\n') {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        self.current += 1;
+    }
+
+    fn expected(&self, what: &str) -> (usize, usize, String) {
+        match self.peek() {
+            Some(c) => self.error(&format!("Expect {}, found '{}'", what, c)),
+            None => self.error(&format!("Expect {}, found end of input", what)),
+        }
+    }
+
+    fn error(&self, message: &str) -> (usize, usize, String) {
+        (self.line, self.column, message.to_string())
+    }
+}
+
+struct Writer {
+    indent: Option<String>,
+    out: String,
+    // Identities of the containers currently being written, to catch cycles.
+    path: Vec<usize>,
+}
+
+impl Writer {
+    fn value(&mut self, value: &Literal, depth: usize) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err(format!(
+                "Cannot stringify values nested more than {} deep.",
+                MAX_DEPTH
+            ));
+        }
+        match value {
+            Literal::Nothing => self.out.push_str("null"),
+            Literal::Bool(b) => self.out.push_str(&b.to_string()),
+            Literal::Number(n) if n.is_finite() => self.out.push_str(&n.to_string()),
+            Literal::Number(n) => return Err(format!("Cannot stringify the number {}.", n)),
+            Literal::Str(s) => self.string(s),
+            Literal::List(list) => {
+                self.enter(value)?;
+                let items = list.borrow().clone();
+                self.out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.newline(depth + 1);
+                    self.value(item, depth + 1)?;
+                }
+                if !items.is_empty() {
+                    self.newline(depth);
+                }
+                self.out.push(']');
+                self.path.pop();
+            }
+            Literal::Map(map) => {
+                self.enter(value)?;
+                let entries: Vec<(String, Literal)> = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                self.entries(&entries, depth)?;
+                self.path.pop();
+            }
+            Literal::Instance(Instance::Dynamic(object)) => {
+                self.enter(value)?;
+                let mut entries: Vec<(String, Literal)> = object
+                    .borrow()
+                    .fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                entries.sort_by(|(l, _), (r, _)| l.cmp(r));
+                self.entries(&entries, depth)?;
+                self.path.pop();
+            }
+            value => return Err(format!("Cannot stringify {}.", value)),
+        }
+        Ok(())
+    }
+
+    fn entries(&mut self, entries: &[(String, Literal)], depth: usize) -> Result<(), String> {
+        self.out.push('{');
+        for (i, (key, value)) in entries.iter().enumerate() {
+            if i > 0 {
+                self.out.push(',');
+            }
+            self.newline(depth + 1);
+            self.string(key);
+            self.out.push(':');
+            if self.indent.is_some() {
+                self.out.push(' ');
+            }
+            self.value(value, depth + 1)?;
+        }
+        if !entries.is_empty() {
+            self.newline(depth);
+        }
+        self.out.push('}');
+        Ok(())
+    }
+
+    fn enter(&mut self, value: &Literal) -> Result<(), String> {
+        let id = value.identity().unwrap_or_default();
+        if self.path.contains(&id) {
+            return Err("Cannot stringify a cyclic structure.".to_string());
+        }
+        self.path.push(id);
+        Ok(())
+    }
+
+    fn newline(&mut self, depth: usize) {
+        if let Some(indent) = &self.indent {
+            self.out.push('\n');
+            self.out.push_str(&indent.repeat(depth));
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.out.push_str(&quote(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(text: &str) -> ParseResult<Literal> {
+        Reader::new(text).document()
+    }
+
+    fn write(value: &Literal, indent: Option<&str>) -> Result<String, String> {
+        let mut writer = Writer {
+            indent: indent.map(str::to_string),
+            out: String::new(),
+            path: Vec::new(),
+        };
+        writer.value(value, 0)?;
+        Ok(writer.out)
+    }
+
+    fn list(items: Vec<Literal>) -> Literal {
+        Literal::List(Rc::new(RefCell::new(items)))
+    }
+
+    fn nested(depth: usize) -> Literal {
+        let mut value = list(Vec::new());
+        for _ in 0..depth {
+            value = list(vec![value]);
+        }
+        value
+    }
+
+    #[test]
+    fn round_trips() {
+        let text = r#"{"a":[1,2.5,-0.25],"b":{"c":null,"d":true},"e":"x\"y\n"}"#;
+        assert_eq!(write(&read(text).unwrap(), None).unwrap(), text);
+        let pretty = write(&read("[1,{\"k\":[]}]").unwrap(), Some("  ")).unwrap();
+        assert_eq!(pretty, "[\n  1,\n  {\n    \"k\": []\n  }\n]");
+    }
+
+    #[test]
+    fn strings_and_escapes() {
+        let value = read(r#""\u00e9\ud83d\ude00\t\/""#).unwrap();
+        assert_eq!(value, Literal::Str("\u{e9}\u{1f600}\t/".to_string()));
+        assert_eq!(quote("\u{1}\u{8}"), r#""\u0001\b""#);
+        assert_eq!(
+            read(r#""\ud83d""#).unwrap_err().2,
+            "Unpaired surrogate in string"
+        );
+        assert_eq!(
+            read("\"a\nb\"").unwrap_err().2,
+            "Control character in string"
+        );
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(
+            read("[1,\n  2,]").unwrap_err(),
+            (2, 5, "Unexpected character ']'".to_string())
+        );
+        assert_eq!(read("01").unwrap_err().2, "Unexpected character '1'");
+        assert_eq!(read("").unwrap_err().2, "Unexpected end of input");
+        assert_eq!(
+            read("nul").unwrap_err(),
+            (1, 1, "Invalid literal".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_depth_limit() {
+        let deepest = "[".repeat(MAX_DEPTH + 1) + &"]".repeat(MAX_DEPTH + 1);
+        assert!(read(&deepest).is_ok());
+        let too_deep = "[".repeat(MAX_DEPTH + 2) + &"]".repeat(MAX_DEPTH + 2);
+        let err = read(&too_deep).unwrap_err();
+        assert_eq!(err, (1, MAX_DEPTH + 2, "Nesting is too deep".to_string()));
+        assert!(read(&"[".repeat(200_000)).is_err());
+    }
+
+    #[test]
+    fn stringify_depth_limit() {
+        assert!(write(&nested(MAX_DEPTH), None).is_ok());
+        let err = write(&nested(MAX_DEPTH + 1), None).unwrap_err();
+        assert_eq!(err, "Cannot stringify values nested more than 512 deep.");
+    }
+
+    #[test]
+    fn cycles() {
+        let inner = list(vec![Literal::Number(1.0)]);
+        let shared = list(vec![inner.clone(), inner.clone()]);
+        assert_eq!(write(&shared, None).unwrap(), "[[1],[1]]");
+        if let Literal::List(items) = &inner {
+            items.borrow_mut().push(shared.clone());
+        }
+        let err = write(&shared, None).unwrap_err();
+        assert_eq!(err, "Cannot stringify a cyclic structure.");
+        let map = Literal::Map(Rc::new(RefCell::new(BTreeMap::new())));
+        if let Literal::Map(entries) = &map {
+            entries.borrow_mut().insert("self".to_string(), map.clone());
+        }
+        assert_eq!(write(&map, None).unwrap_err(), err);
+    }
+
+    #[test]
+    fn rejects_non_finite_numbers() {
+        let err = write(&Literal::Number(f64::NAN), None).unwrap_err();
+        assert_eq!(err, "Cannot stringify the number NaN.");
+    }
+}
@@ -0,0 +1,79 @@
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::{Arity, Native, NativeFn};
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn method(name: &str) -> Option<Native> {
+    let (arity, function): (Arity, NativeFn) = match name {
+        "length" => (Arity::Fixed(0), length),
+        "get" => (Arity::Fixed(1), get),
+        "join" => (Arity::Fixed(1), join),
+        _ => return None,
+    };
+    Some(Native::new(name, arity, function))
+}
+
+fn length(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let list = receiver(paren, args)?;
+    let length = list.borrow().len();
+    Ok(Literal::Number(length as f64))
+}
+
+fn get(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let list = receiver(paren, args)?;
+    let list = list.borrow();
+    match &args[1] {
+        Literal::Number(index)
+            if index.fract() == 0.0 && *index >= 0.0 && (*index as usize) < list.len() =>
+        {
+            Ok(list[*index as usize].clone())
+        }
+        Literal::Number(index) => Err(RuntimeError::new(
+            ErrorKind::IndexError,
+            paren.clone(),
+            &format!("List index {} out of range.", index),
+        )),
+        _ => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            "List index must be a number.",
+        )),
+    }
+}
+
+fn join(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let list = receiver(paren, args)?;
+    let separator = match &args[1] {
+        Literal::Str(separator) => separator,
+        _ => {
+            return Err(RuntimeError::new(
+                ErrorKind::TypeError,
+                paren.clone(),
+                "Argument to 'join' must be a string.",
+            ))
+        }
+    };
+    let items: Vec<String> = list
+        .borrow()
+        .iter()
+        .map(|item| match item {
+            Literal::Str(s) => s.clone(),
+            item => item.to_string(),
+        })
+        .collect();
+    Ok(Literal::Str(items.join(separator)))
+}
+
+fn receiver(paren: &Token, args: &[Literal]) -> RuntimeResult<Rc<RefCell<Vec<Literal>>>> {
+    match &args[0] {
+        Literal::List(list) => Ok(Rc::clone(list)),
+        _ => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            "Expect a list.",
+        )),
+    }
+}
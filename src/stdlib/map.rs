@@ -0,0 +1,91 @@
+use super::expect_str;
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::{Arity, Native, NativeFn};
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+pub fn method(name: &str) -> Option<Native> {
+    let (arity, function): (Arity, NativeFn) = match name {
+        "length" => (Arity::Fixed(0), length),
+        "get" => (Arity::Fixed(1), get),
+        "set" => (Arity::Fixed(2), set),
+        "has" => (Arity::Fixed(1), has),
+        "remove" => (Arity::Fixed(1), remove),
+        "keys" => (Arity::Fixed(0), keys),
+        "values" => (Arity::Fixed(0), values),
+        _ => return None,
+    };
+    Some(Native::new(name, arity, function))
+}
+
+pub fn new(_: &mut Interpreter, _: &Token, _: &[Literal]) -> RuntimeResult<Literal> {
+    Ok(Literal::Map(Rc::new(RefCell::new(BTreeMap::new()))))
+}
+
+fn length(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let map = receiver(paren, args)?;
+    let length = map.borrow().len();
+    Ok(Literal::Number(length as f64))
+}
+
+fn get(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let map = receiver(paren, args)?;
+    let key = expect_str(paren, "get", &args[1])?;
+    let value = map.borrow().get(key).cloned();
+    Ok(value.unwrap_or(Literal::Nothing))
+}
+
+fn set(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let map = receiver(paren, args)?;
+    let key = expect_str(paren, "set", &args[1])?;
+    map.borrow_mut().insert(key.to_string(), args[2].clone());
+    Ok(args[2].clone())
+}
+
+fn has(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let map = receiver(paren, args)?;
+    let key = expect_str(paren, "has", &args[1])?;
+    let found = map.borrow().contains_key(key);
+    Ok(Literal::Bool(found))
+}
+
+fn remove(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let map = receiver(paren, args)?;
+    let key = expect_str(paren, "remove", &args[1])?;
+    let value = map.borrow_mut().remove(key);
+    Ok(value.unwrap_or(Literal::Nothing))
+}
+
+fn keys(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let map = receiver(paren, args)?;
+    let keys = map
+        .borrow()
+        .keys()
+        .map(|key| Literal::Str(key.clone()))
+        .collect();
+    Ok(Literal::List(Rc::new(RefCell::new(keys))))
+}
+
+fn values(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let map = receiver(paren, args)?;
+    let values = map.borrow().values().cloned().collect();
+    Ok(Literal::List(Rc::new(RefCell::new(values))))
+}
+
+fn receiver(
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Rc<RefCell<BTreeMap<String, Literal>>>> {
+    match &args[0] {
+        Literal::Map(map) => Ok(Rc::clone(map)),
+        _ => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            "Expect a map.",
+        )),
+    }
+}
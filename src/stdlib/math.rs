@@ -0,0 +1,183 @@
+use super::{expect_number, module, native};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::Arity;
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::f64::consts;
+
+pub fn load(globals: &mut Environment) {
+    let math = module(
+        "math",
+        vec![
+            ("PI", Literal::Number(consts::PI)),
+            ("E", Literal::Number(consts::E)),
+            ("INFINITY", Literal::Number(f64::INFINITY)),
+            ("NAN", Literal::Number(f64::NAN)),
+            ("sqrt", native("sqrt", Arity::Fixed(1), sqrt)),
+            ("cbrt", native("cbrt", Arity::Fixed(1), cbrt)),
+            ("pow", native("pow", Arity::Fixed(2), pow)),
+            ("abs", native("abs", Arity::Fixed(1), abs)),
+            ("sign", native("sign", Arity::Fixed(1), sign)),
+            ("floor", native("floor", Arity::Fixed(1), floor)),
+            ("ceil", native("ceil", Arity::Fixed(1), ceil)),
+            ("round", native("round", Arity::Fixed(1), round)),
+            ("trunc", native("trunc", Arity::Fixed(1), trunc)),
+            ("toInt", native("toInt", Arity::Fixed(1), to_int)),
+            ("min", native("min", Arity::Variadic(1), min)),
+            ("max", native("max", Arity::Variadic(1), max)),
+            ("sin", native("sin", Arity::Fixed(1), sin)),
+            ("cos", native("cos", Arity::Fixed(1), cos)),
+            ("tan", native("tan", Arity::Fixed(1), tan)),
+            ("asin", native("asin", Arity::Fixed(1), asin)),
+            ("acos", native("acos", Arity::Fixed(1), acos)),
+            ("atan", native("atan", Arity::Fixed(1), atan)),
+            ("atan2", native("atan2", Arity::Fixed(2), atan2)),
+            ("exp", native("exp", Arity::Fixed(1), exp)),
+            ("log", native("log", Arity::Fixed(1), log)),
+            ("log2", native("log2", Arity::Fixed(1), log2)),
+            ("log10", native("log10", Arity::Fixed(1), log10)),
+            ("isNaN", native("isNaN", Arity::Fixed(1), is_nan)),
+            ("isFinite", native("isFinite", Arity::Fixed(1), is_finite)),
+        ],
+    );
+    globals.define("math".to_string(), math);
+}
+
+fn unary(
+    paren: &Token,
+    name: &str,
+    args: &[Literal],
+    op: fn(f64) -> f64,
+) -> RuntimeResult<Literal> {
+    Ok(Literal::Number(op(expect_number(paren, name, &args[0])?)))
+}
+
+fn sqrt(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "sqrt", args, f64::sqrt)
+}
+
+fn cbrt(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "cbrt", args, f64::cbrt)
+}
+
+fn pow(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let base = expect_number(paren, "pow", &args[0])?;
+    let exponent = expect_number(paren, "pow", &args[1])?;
+    Ok(Literal::Number(base.powf(exponent)))
+}
+
+fn abs(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "abs", args, f64::abs)
+}
+
+fn sign(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let n = expect_number(paren, "sign", &args[0])?;
+    if n == 0.0 || n.is_nan() {
+        return Ok(Literal::Number(n));
+    }
+    Ok(Literal::Number(n.signum()))
+}
+
+fn floor(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "floor", args, f64::floor)
+}
+
+fn ceil(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "ceil", args, f64::ceil)
+}
+
+fn round(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "round", args, f64::round)
+}
+
+fn trunc(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "trunc", args, f64::trunc)
+}
+
+fn to_int(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let n = expect_number(paren, "toInt", &args[0])?;
+    if !n.is_finite() {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            &format!("Cannot convert {} to an integer.", n),
+        ));
+    }
+    Ok(Literal::Number(n.trunc()))
+}
+
+fn min(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let mut result = f64::INFINITY;
+    for arg in args {
+        result = result.min(expect_number(paren, "min", arg)?);
+    }
+    Ok(Literal::Number(result))
+}
+
+fn max(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let mut result = f64::NEG_INFINITY;
+    for arg in args {
+        result = result.max(expect_number(paren, "max", arg)?);
+    }
+    Ok(Literal::Number(result))
+}
+
+fn sin(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "sin", args, f64::sin)
+}
+
+fn cos(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "cos", args, f64::cos)
+}
+
+fn tan(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "tan", args, f64::tan)
+}
+
+fn asin(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "asin", args, f64::asin)
+}
+
+fn acos(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "acos", args, f64::acos)
+}
+
+fn atan(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "atan", args, f64::atan)
+}
+
+fn atan2(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let y = expect_number(paren, "atan2", &args[0])?;
+    let x = expect_number(paren, "atan2", &args[1])?;
+    Ok(Literal::Number(y.atan2(x)))
+}
+
+fn exp(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "exp", args, f64::exp)
+}
+
+fn log(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "log", args, f64::ln)
+}
+
+fn log2(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "log2", args, f64::log2)
+}
+
+fn log10(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    unary(paren, "log10", args, f64::log10)
+}
+
+fn is_nan(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    Ok(Literal::Bool(
+        expect_number(paren, "isNaN", &args[0])?.is_nan(),
+    ))
+}
+
+fn is_finite(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    Ok(Literal::Bool(
+        expect_number(paren, "isFinite", &args[0])?.is_finite(),
+    ))
+}
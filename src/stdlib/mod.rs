@@ -0,0 +1,88 @@
+pub mod errors;
+pub mod fs;
+pub mod globals;
+pub mod io;
+pub mod json;
+pub mod list;
+pub mod map;
+pub mod math;
+pub mod process;
+pub mod random;
+pub mod record;
+pub mod regex;
+pub mod string;
+pub mod time;
+
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::class::Class;
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::RuntimeResult;
+use crate::interpreter::native::{Arity, Native, NativeFn};
+use crate::interpreter::object::Object;
+use crate::lexer::literal::{Instance, Literal};
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub fn load(globals: &mut Environment) {
+    globals::load(globals);
+    math::load(globals);
+    fs::load(globals);
+    io::load(globals);
+    process::load(globals);
+    json::load(globals);
+    random::load(globals);
+    time::load(globals);
+    errors::load(globals);
+}
+
+pub fn define(environment: &mut Environment, name: &str, arity: Arity, function: NativeFn) {
+    environment.define(name.to_string(), native(name, arity, function));
+}
+
+pub fn native(name: &str, arity: Arity, function: NativeFn) -> Literal {
+    Literal::Native(Rc::new(Native::new(name, arity, function)))
+}
+
+pub fn module(name: &str, members: Vec<(&str, Literal)>) -> Literal {
+    let class = Class::new(name.to_string(), HashMap::new(), HashMap::new(), None);
+    let mut object = Object::new(Rc::new(RefCell::new(class)));
+    for (member, value) in members {
+        object.fields.insert(member.to_string(), value);
+    }
+    Literal::Instance(Instance::Dynamic(Rc::new(RefCell::new(object))))
+}
+
+pub fn expect_number(paren: &Token, function: &str, value: &Literal) -> RuntimeResult<f64> {
+    match value {
+        Literal::Number(n) => Ok(*n),
+        _ => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            &format!("Argument to '{}' must be a number.", function),
+        )),
+    }
+}
+
+pub fn expect_str<'a>(paren: &Token, function: &str, value: &'a Literal) -> RuntimeResult<&'a str> {
+    match value {
+        Literal::Str(s) => Ok(s),
+        _ => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            &format!("Argument to '{}' must be a string.", function),
+        )),
+    }
+}
+
+pub fn expect_index(paren: &Token, function: &str, value: &Literal) -> RuntimeResult<i64> {
+    match value {
+        Literal::Number(n) if n.fract() == 0.0 => Ok(*n as i64),
+        _ => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            &format!("Argument to '{}' must be an integer.", function),
+        )),
+    }
+}
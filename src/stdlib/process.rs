@@ -0,0 +1,71 @@
+use super::{define, expect_index, expect_str};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::Arity;
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::env;
+use std::rc::Rc;
+
+pub fn load(globals: &mut Environment) {
+    define_args(globals, &[]);
+    define(globals, "env", Arity::Fixed(1), get_env);
+    define(globals, "setEnv", Arity::Fixed(2), set_env);
+    define(globals, "exit", Arity::Fixed(1), exit);
+}
+
+pub fn define_args(globals: &mut Environment, args: &[String]) {
+    let args = args.iter().map(|arg| Literal::Str(arg.clone())).collect();
+    globals.define(
+        "args".to_string(),
+        Literal::List(Rc::new(RefCell::new(args))),
+    );
+}
+
+fn get_env(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let name = expect_str(paren, "env", &args[0])?;
+    match env::var(name) {
+        Ok(value) => Ok(Literal::Str(value)),
+        Err(_) => Ok(Literal::Nothing),
+    }
+}
+
+fn set_env(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let name = expect_str(paren, "setEnv", &args[0])?;
+    if name.is_empty() || name.contains('=') || name.contains('\0') {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            &format!("Invalid environment variable name '{}'.", name),
+        ));
+    }
+    match &args[1] {
+        Literal::Nothing => env::remove_var(name),
+        value => {
+            let value = expect_str(paren, "setEnv", value)?;
+            if value.contains('\0') {
+                return Err(RuntimeError::new(
+                    ErrorKind::ValueError,
+                    paren.clone(),
+                    "Environment variable values cannot contain NUL.",
+                ));
+            }
+            env::set_var(name, value);
+        }
+    }
+    Ok(Literal::Nothing)
+}
+
+fn exit(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let code = expect_index(paren, "exit", &args[0])?;
+    if !(0..=255).contains(&code) {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            "Exit code must be between 0 and 255.",
+        ));
+    }
+    Err(RuntimeError::exit(paren.clone(), code as i32))
+}
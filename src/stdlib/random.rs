@@ -0,0 +1,165 @@
+use super::{define, expect_index};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::Arity;
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// xoshiro256** (Blackman and Vigna), seeded through splitmix64 so that any
+// 64-bit seed, including zero, gives a well-mixed state.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        let mut mix = seed;
+        let mut state = [0; 4];
+        for word in state.iter_mut() {
+            mix = mix.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = mix;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            *word = z ^ (z >> 31);
+        }
+        Rng { state }
+    }
+
+    pub fn from_time() -> Rng {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Rng::new(now.as_nanos() as u64)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    // Uniform in [0, 1), using the top 53 bits.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    // Uniform in [0, bound), rejecting the biased tail of the range.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let n = self.next_u64();
+            if n < zone {
+                return n % bound;
+            }
+        }
+    }
+}
+
+// Beyond 2^53 not every integer is a representable number.
+const MAX_SAFE_INTEGER: i64 = 1 << 53;
+
+pub fn load(globals: &mut Environment) {
+    define(globals, "random", Arity::Fixed(0), random);
+    define(globals, "randomInt", Arity::Fixed(2), random_int);
+    define(globals, "shuffle", Arity::Fixed(1), shuffle);
+    define(globals, "choice", Arity::Fixed(1), choice);
+    define(globals, "seed", Arity::Fixed(1), seed);
+}
+
+fn random(interpreter: &mut Interpreter, _: &Token, _: &[Literal]) -> RuntimeResult<Literal> {
+    Ok(Literal::Number(interpreter.rng.next_f64()))
+}
+
+// Both bounds are inclusive.
+fn random_int(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let lo = expect_index(paren, "randomInt", &args[0])?;
+    let hi = expect_index(paren, "randomInt", &args[1])?;
+    if lo.abs() > MAX_SAFE_INTEGER || hi.abs() > MAX_SAFE_INTEGER {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            "Bounds of 'randomInt' must be exactly representable integers.",
+        ));
+    }
+    if lo > hi {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            "Lower bound of 'randomInt' must not exceed the upper bound.",
+        ));
+    }
+    let span = (hi - lo) as u64 + 1;
+    let offset = interpreter.rng.below(span);
+    Ok(Literal::Number((lo + offset as i64) as f64))
+}
+
+fn shuffle(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let list = expect_list(paren, "shuffle", &args[0])?;
+    {
+        let mut items = list.borrow_mut();
+        for i in (1..items.len()).rev() {
+            let j = interpreter.rng.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+    Ok(Literal::List(list))
+}
+
+fn choice(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let list = expect_list(paren, "choice", &args[0])?;
+    let items = list.borrow();
+    if items.is_empty() {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            "Cannot choose from an empty list.",
+        ));
+    }
+    let index = interpreter.rng.below(items.len() as u64) as usize;
+    Ok(items[index].clone())
+}
+
+fn seed(interpreter: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let seed = expect_index(paren, "seed", &args[0])?;
+    interpreter.rng = Rng::new(seed as u64);
+    Ok(Literal::Nothing)
+}
+
+fn expect_list(
+    paren: &Token,
+    function: &str,
+    value: &Literal,
+) -> RuntimeResult<Rc<RefCell<Vec<Literal>>>> {
+    match value {
+        Literal::List(list) => Ok(Rc::clone(list)),
+        _ => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            &format!("Argument to '{}' must be a list.", function),
+        )),
+    }
+}
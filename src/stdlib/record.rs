@@ -0,0 +1,35 @@
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::{Arity, Native};
+use crate::lexer::literal::{Instance, Literal};
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub fn method(name: &str) -> Option<Native> {
+    match name {
+        "toString" => Some(Native::new(name, Arity::Fixed(0), to_string)),
+        "with" => Some(Native::new(name, Arity::Variadic(0), with)),
+        _ => None,
+    }
+}
+
+fn to_string(_: &mut Interpreter, _: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    Ok(Literal::Str(args[0].to_string()))
+}
+
+fn with(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    match &args[0] {
+        Literal::Instance(Instance::Dynamic(object)) => {
+            let copy = object.borrow().with(paren, &args[1..])?;
+            Ok(Literal::Instance(Instance::Dynamic(Rc::new(RefCell::new(
+                copy,
+            )))))
+        }
+        _ => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            "Expect a record.",
+        )),
+    }
+}
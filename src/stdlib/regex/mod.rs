@@ -0,0 +1,239 @@
+pub mod pattern;
+
+use self::pattern::{Captures, Regex};
+use super::{expect_str, module};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::{Arity, Native, NativeFn};
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+pub fn method(name: &str) -> Option<Native> {
+    let (arity, function): (Arity, NativeFn) = match name {
+        "test" => (Arity::Fixed(1), test),
+        "find" => (Arity::Fixed(1), find),
+        "findAll" => (Arity::Fixed(1), find_all),
+        "replace" => (Arity::Fixed(2), replace),
+        "split" => (Arity::Fixed(1), split),
+        "source" => (Arity::Fixed(0), source),
+        _ => return None,
+    };
+    Some(Native::new(name, arity, function))
+}
+
+pub fn new(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let source = expect_str(paren, "Regex", &args[0])?;
+    match Regex::new(source) {
+        Ok(regex) => Ok(Literal::Regex(Rc::new(regex))),
+        Err(err) => Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            &format!(
+                "Invalid regex at position {}: {}.",
+                err.position, err.message
+            ),
+        )),
+    }
+}
+
+fn test(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let regex = receiver(paren, args)?;
+    let chars: Vec<char> = expect_str(paren, "test", &args[1])?.chars().collect();
+    let found = regex.matcher(&chars).find_at(0).is_some();
+    Ok(Literal::Bool(found))
+}
+
+fn find(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let regex = receiver(paren, args)?;
+    let chars: Vec<char> = expect_str(paren, "find", &args[1])?.chars().collect();
+    match regex.matcher(&chars).find_at(0) {
+        Some(captures) => Ok(match_object(&regex, &chars, &captures)),
+        None => Ok(Literal::Nothing),
+    }
+}
+
+fn find_all(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let regex = receiver(paren, args)?;
+    let chars: Vec<char> = expect_str(paren, "findAll", &args[1])?.chars().collect();
+    let matches = all_matches(&regex, &chars)
+        .iter()
+        .map(|captures| match_object(&regex, &chars, captures))
+        .collect();
+    Ok(Literal::List(Rc::new(RefCell::new(matches))))
+}
+
+// The replacement is either a string, where $0-$9, $<name> and $$ refer to
+// the match, or a function that takes the match object and returns a string.
+fn replace(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let regex = receiver(paren, args)?;
+    let chars: Vec<char> = expect_str(paren, "replace", &args[1])?.chars().collect();
+    let mut result = String::new();
+    let mut last = 0;
+    for captures in all_matches(&regex, &chars) {
+        let (start, end) = bounds(&captures, 0).unwrap();
+        result.extend(&chars[last..start]);
+        match &args[2] {
+            Literal::Str(template) => expand(&regex, &chars, &captures, template, &mut result),
+            callee => {
+                let found = match_object(&regex, &chars, &captures);
+                match interpreter.call(callee.clone(), paren, &vec![found])? {
+                    Literal::Str(s) => result.push_str(&s),
+                    _ => {
+                        return Err(RuntimeError::new(
+                            ErrorKind::TypeError,
+                            paren.clone(),
+                            "Replacement function must return a string.",
+                        ))
+                    }
+                }
+            }
+        }
+        last = end;
+    }
+    result.extend(&chars[last..]);
+    Ok(Literal::Str(result))
+}
+
+fn split(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let regex = receiver(paren, args)?;
+    let chars: Vec<char> = expect_str(paren, "split", &args[1])?.chars().collect();
+    let mut parts = Vec::new();
+    let mut last = 0;
+    for captures in all_matches(&regex, &chars) {
+        let (start, end) = bounds(&captures, 0).unwrap();
+        // An empty match at either end would only add an empty piece.
+        if start == end && (start == 0 || start == chars.len()) {
+            continue;
+        }
+        parts.push(Literal::Str(chars[last..start].iter().collect()));
+        last = end;
+    }
+    parts.push(Literal::Str(chars[last..].iter().collect()));
+    Ok(Literal::List(Rc::new(RefCell::new(parts))))
+}
+
+fn source(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let regex = receiver(paren, args)?;
+    Ok(Literal::Str(regex.source().to_string()))
+}
+
+// Non-overlapping matches from left to right. After an empty match the
+// search resumes one char further on so that it always makes progress.
+fn all_matches(regex: &Regex, chars: &[char]) -> Vec<Captures> {
+    let mut matcher = regex.matcher(chars);
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start <= chars.len() {
+        let captures = match matcher.find_at(start) {
+            Some(captures) => captures,
+            None => break,
+        };
+        let (begin, end) = bounds(&captures, 0).unwrap();
+        start = if begin == end { end + 1 } else { end };
+        matches.push(captures);
+    }
+    matches
+}
+
+fn bounds(captures: &Captures, group: usize) -> Option<(usize, usize)> {
+    match (captures[2 * group], captures[2 * group + 1]) {
+        (Some(start), Some(end)) => Some((start, end)),
+        _ => None,
+    }
+}
+
+fn group_text(chars: &[char], captures: &Captures, group: usize) -> Option<String> {
+    bounds(captures, group).map(|(start, end)| chars[start..end].iter().collect())
+}
+
+// A match is an object with `text`, `start` and `end` (char offsets), a
+// `groups` list holding each capture group (nil when it did not take part),
+// and a `named` map for the named groups.
+fn match_object(regex: &Regex, chars: &[char], captures: &Captures) -> Literal {
+    let (start, end) = bounds(captures, 0).unwrap();
+    let text = |group| match group_text(chars, captures, group) {
+        Some(text) => Literal::Str(text),
+        None => Literal::Nothing,
+    };
+    let groups = (1..=regex.groups()).map(text).collect();
+    let named: BTreeMap<String, Literal> = regex
+        .names()
+        .iter()
+        .map(|(name, group)| (name.clone(), text(*group)))
+        .collect();
+    module(
+        "match",
+        vec![
+            ("text", text(0)),
+            ("start", Literal::Number(start as f64)),
+            ("end", Literal::Number(end as f64)),
+            ("groups", Literal::List(Rc::new(RefCell::new(groups)))),
+            ("named", Literal::Map(Rc::new(RefCell::new(named)))),
+        ],
+    )
+}
+
+fn expand(regex: &Regex, chars: &[char], captures: &Captures, template: &str, out: &mut String) {
+    let template: Vec<char> = template.chars().collect();
+    let mut i = 0;
+    while i < template.len() {
+        if template[i] != '$' || i + 1 == template.len() {
+            out.push(template[i]);
+            i += 1;
+            continue;
+        }
+        match template[i + 1] {
+            '$' => {
+                out.push('$');
+                i += 2;
+            }
+            d if d.is_ascii_digit() => {
+                let group = d.to_digit(10).unwrap() as usize;
+                if group <= regex.groups() {
+                    out.push_str(&group_text(chars, captures, group).unwrap_or_default());
+                } else {
+                    out.push('$');
+                    out.push(d);
+                }
+                i += 2;
+            }
+            '<' => {
+                let close = template[i + 2..].iter().position(|c| *c == '>');
+                let name: Option<String> =
+                    close.map(|close| template[i + 2..i + 2 + close].iter().collect());
+                match name.as_ref().and_then(|name| regex.group_index(name)) {
+                    Some(group) => {
+                        out.push_str(&group_text(chars, captures, group).unwrap_or_default());
+                        i += 3 + close.unwrap();
+                    }
+                    None => {
+                        out.push('$');
+                        i += 1;
+                    }
+                }
+            }
+            _ => {
+                out.push('$');
+                i += 1;
+            }
+        }
+    }
+}
+
+fn receiver(paren: &Token, args: &[Literal]) -> RuntimeResult<Rc<Regex>> {
+    match &args[0] {
+        Literal::Regex(regex) => Ok(Rc::clone(regex)),
+        _ => Err(RuntimeError::new(
+            ErrorKind::TypeError,
+            paren.clone(),
+            "Expect a regex.",
+        )),
+    }
+}
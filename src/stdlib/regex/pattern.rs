@@ -0,0 +1,735 @@
+use std::collections::HashSet;
+use std::fmt;
+
+// Counted repetitions are expanded inline, so keep them bounded.
+const MAX_REPEAT: usize = 1000;
+const MAX_PROGRAM: usize = 100_000;
+// Parsing and compiling recurse once per group, so deeper nesting is
+// rejected instead of overflowing the native stack.
+const MAX_DEPTH: usize = 256;
+
+pub struct CompileError {
+    pub position: usize,
+    pub message: String,
+}
+
+// A compiled pattern, matched by a backtracking VM over the compiled program.
+#[derive(Debug)]
+pub struct Regex {
+    source: String,
+    program: Vec<Inst>,
+    groups: usize,
+    names: Vec<(String, usize)>,
+}
+
+// Capture slots of a successful match: for group n, `slots[2n]` and
+// `slots[2n + 1]` hold the char offsets where it starts and ends.
+pub type Captures = Vec<Option<usize>>;
+
+#[derive(Debug, Clone)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Assert(Assertion),
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>),
+    Repeat(Box<Node>, usize, Option<usize>, bool),
+}
+
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Assertion {
+    Start,
+    End,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Assert(Assertion),
+    Split(usize, usize),
+    Jump(usize),
+    Save(usize),
+    Match,
+}
+
+impl Regex {
+    pub fn new(source: &str) -> Result<Regex, CompileError> {
+        let mut parser = PatternParser {
+            chars: source.chars().collect(),
+            current: 0,
+            depth: 0,
+            groups: 0,
+            names: Vec::new(),
+        };
+        let node = parser.parse()?;
+        let mut program = vec![Inst::Save(0)];
+        compile(&node, &mut program);
+        program.push(Inst::Save(1));
+        program.push(Inst::Match);
+        if program.len() > MAX_PROGRAM {
+            return Err(CompileError {
+                position: 0,
+                message: "Pattern is too large".to_string(),
+            });
+        }
+        Ok(Regex {
+            source: source.to_string(),
+            program,
+            groups: parser.groups,
+            names: parser.names,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    // Number of capture groups, not counting the whole match.
+    pub fn groups(&self) -> usize {
+        self.groups
+    }
+
+    pub fn names(&self) -> &[(String, usize)] {
+        &self.names
+    }
+
+    pub fn group_index(&self, name: &str) -> Option<usize> {
+        self.names
+            .iter()
+            .find(|(group, _)| group == name)
+            .map(|(_, index)| *index)
+    }
+
+    pub fn matcher<'a>(&'a self, input: &'a [char]) -> Matcher<'a> {
+        Matcher {
+            program: &self.program,
+            input,
+            slot_count: 2 * (self.groups + 1),
+            failed: HashSet::new(),
+            visited: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "/{}/", self.source)
+    }
+}
+
+struct PatternParser {
+    chars: Vec<char>,
+    current: usize,
+    depth: usize,
+    groups: usize,
+    names: Vec<(String, usize)>,
+}
+
+impl PatternParser {
+    fn parse(&mut self) -> Result<Node, CompileError> {
+        let node = self.alternation()?;
+        match self.peek() {
+            None => Ok(node),
+            Some(_) => Err(self.error("Unmatched ')'")),
+        }
+    }
+
+    fn alternation(&mut self) -> Result<Node, CompileError> {
+        let mut branches = vec![self.concatenation()?];
+        while self.matches('|') {
+            branches.push(self.concatenation()?);
+        }
+        if branches.len() == 1 {
+            return Ok(branches.pop().unwrap());
+        }
+        Ok(Node::Alternate(branches))
+    }
+
+    fn concatenation(&mut self) -> Result<Node, CompileError> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            nodes.push(self.repetition()?);
+        }
+        match nodes.len() {
+            0 => Ok(Node::Empty),
+            1 => Ok(nodes.pop().unwrap()),
+            _ => Ok(Node::Concat(nodes)),
+        }
+    }
+
+    fn repetition(&mut self) -> Result<Node, CompileError> {
+        let start = self.current;
+        let atom = self.atom()?;
+        let (min, max) = match self.peek() {
+            Some('{') if self.peek_next().is_some_and(|c| c.is_ascii_digit()) => self.counted()?,
+            Some(c @ '*') | Some(c @ '+') | Some(c @ '?') => {
+                self.current += 1;
+                match c {
+                    '*' => (0, None),
+                    '+' => (1, None),
+                    _ => (0, Some(1)),
+                }
+            }
+            _ => return Ok(atom),
+        };
+        if let Node::Assert(_) = atom {
+            return Err(CompileError {
+                position: start,
+                message: "Nothing to repeat".to_string(),
+            });
+        }
+        let greedy = !self.matches('?');
+        if let Some('*') | Some('+') | Some('{') = self.peek() {
+            return Err(self.error("Nested quantifier"));
+        }
+        Ok(Node::Repeat(Box::new(atom), min, max, greedy))
+    }
+
+    fn counted(&mut self) -> Result<(usize, Option<usize>), CompileError> {
+        let start = self.current;
+        self.current += 1;
+        let min = self.number()?;
+        let max = if self.matches(',') {
+            match self.peek() {
+                Some('}') => None,
+                _ => Some(self.number()?),
+            }
+        } else {
+            Some(min)
+        };
+        if !self.matches('}') {
+            return Err(self.error("Expect '}' after repetition count"));
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(CompileError {
+                position: start,
+                message: "Repetition range is out of order".to_string(),
+            });
+        }
+        if min > MAX_REPEAT || max.is_some_and(|max| max > MAX_REPEAT) {
+            return Err(CompileError {
+                position: start,
+                message: format!("Repetition count exceeds {}", MAX_REPEAT),
+            });
+        }
+        Ok((min, max))
+    }
+
+    fn number(&mut self) -> Result<usize, CompileError> {
+        let start = self.current;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.current += 1;
+        }
+        if start == self.current {
+            return Err(self.error("Expect a number in repetition"));
+        }
+        let digits: String = self.chars[start..self.current].iter().collect();
+        digits.parse().map_err(|_| CompileError {
+            position: start,
+            message: "Repetition count is too large".to_string(),
+        })
+    }
+
+    fn atom(&mut self) -> Result<Node, CompileError> {
+        let start = self.current;
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.error("Unexpected end of pattern")),
+        };
+        self.current += 1;
+        match c {
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Assert(Assertion::Start)),
+            '$' => Ok(Node::Assert(Assertion::End)),
+            '(' => self.group(start),
+            '[' => self.class(start),
+            '\\' => self.escape(),
+            '*' | '+' | '?' => Err(CompileError {
+                position: start,
+                message: "Nothing to repeat".to_string(),
+            }),
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    fn group(&mut self, start: usize) -> Result<Node, CompileError> {
+        if self.depth == MAX_DEPTH {
+            return Err(CompileError {
+                position: start,
+                message: format!("Groups are nested more than {} deep", MAX_DEPTH),
+            });
+        }
+        let index = if self.matches('?') {
+            if self.matches(':') {
+                None
+            } else if self.matches('<') {
+                let name_start = self.current;
+                while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                    self.current += 1;
+                }
+                let name: String = self.chars[name_start..self.current].iter().collect();
+                if name.is_empty() || !self.matches('>') {
+                    return Err(self.error("Invalid group name"));
+                }
+                if self.names.iter().any(|(existing, _)| *existing == name) {
+                    return Err(CompileError {
+                        position: name_start,
+                        message: format!("Duplicate group name '{}'", name),
+                    });
+                }
+                self.groups += 1;
+                self.names.push((name, self.groups));
+                Some(self.groups)
+            } else {
+                return Err(self.error("Unknown group syntax"));
+            }
+        } else {
+            self.groups += 1;
+            Some(self.groups)
+        };
+        self.depth += 1;
+        let node = self.alternation()?;
+        self.depth -= 1;
+        if !self.matches(')') {
+            return Err(CompileError {
+                position: start,
+                message: "Unterminated group".to_string(),
+            });
+        }
+        Ok(Node::Group(Box::new(node), index))
+    }
+
+    fn class(&mut self, start: usize) -> Result<Node, CompileError> {
+        let negated = self.matches('^');
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let c = match self.peek() {
+                Some(']') if !first => {
+                    self.current += 1;
+                    return Ok(Node::Class(items, negated));
+                }
+                Some(c) => c,
+                None => {
+                    return Err(CompileError {
+                        position: start,
+                        message: "Unterminated character class".to_string(),
+                    })
+                }
+            };
+            first = false;
+            let low_position = self.current;
+            self.current += 1;
+            let low = if c == '\\' {
+                match self.class_escape()? {
+                    Ok(c) => c,
+                    Err(item) => {
+                        items.push(item);
+                        continue;
+                    }
+                }
+            } else {
+                c
+            };
+            if self.peek() == Some('-') && self.peek_next().is_some_and(|c| c != ']') {
+                self.current += 1;
+                let high = match self.peek() {
+                    Some('\\') => {
+                        self.current += 1;
+                        match self.class_escape()? {
+                            Ok(c) => c,
+                            Err(_) => return Err(self.error("Invalid range in character class")),
+                        }
+                    }
+                    Some(c) => {
+                        self.current += 1;
+                        c
+                    }
+                    None => unreachable!(),
+                };
+                if high < low {
+                    return Err(CompileError {
+                        position: low_position,
+                        message: "Range is out of order in character class".to_string(),
+                    });
+                }
+                items.push(ClassItem::Range(low, high));
+            } else {
+                items.push(ClassItem::Range(low, low));
+            }
+        }
+    }
+
+    // Either a single escaped char or a shorthand class like \d.
+    fn class_escape(&mut self) -> Result<Result<char, ClassItem>, CompileError> {
+        match self.escape()? {
+            Node::Char(c) => Ok(Ok(c)),
+            Node::Class(mut items, false) if items.len() == 1 => Ok(Err(items.pop().unwrap())),
+            _ => Err(CompileError {
+                position: self.current - 2,
+                message: "Invalid escape in character class".to_string(),
+            }),
+        }
+    }
+
+    fn escape(&mut self) -> Result<Node, CompileError> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => {
+                return Err(CompileError {
+                    position: self.current - 1,
+                    message: "Trailing backslash".to_string(),
+                })
+            }
+        };
+        self.current += 1;
+        let node = match c {
+            'd' => Node::Class(vec![ClassItem::Digit(true)], false),
+            'D' => Node::Class(vec![ClassItem::Digit(false)], false),
+            'w' => Node::Class(vec![ClassItem::Word(true)], false),
+            'W' => Node::Class(vec![ClassItem::Word(false)], false),
+            's' => Node::Class(vec![ClassItem::Space(true)], false),
+            'S' => Node::Class(vec![ClassItem::Space(false)], false),
+            'b' => Node::Assert(Assertion::WordBoundary),
+            'B' => Node::Assert(Assertion::NotWordBoundary),
+            'n' => Node::Char('\n'),
+            'r' => Node::Char('\r'),
+            't' => Node::Char('\t'),
+            'f' => Node::Char('\u{c}'),
+            'v' => Node::Char('\u{b}'),
+            '0' => Node::Char('\0'),
+            c if c.is_alphanumeric() => {
+                return Err(CompileError {
+                    position: self.current - 2,
+                    message: format!("Unknown escape '\\{}'", c),
+                })
+            }
+            c => Node::Char(c),
+        };
+        Ok(node)
+    }
+
+    fn matches(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.current).copied()
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        self.chars.get(self.current + 1).copied()
+    }
+
+    fn error(&self, message: &str) -> CompileError {
+        CompileError {
+            position: self.current,
+            message: message.to_string(),
+        }
+    }
+}
+
+fn compile(node: &Node, program: &mut Vec<Inst>) {
+    match node {
+        Node::Empty => {}
+        Node::Char(c) => program.push(Inst::Char(*c)),
+        Node::Any => program.push(Inst::Any),
+        Node::Class(items, negated) => program.push(Inst::Class(items.clone(), *negated)),
+        Node::Assert(assertion) => program.push(Inst::Assert(*assertion)),
+        Node::Group(node, None) => compile(node, program),
+        Node::Group(node, Some(index)) => {
+            program.push(Inst::Save(2 * index));
+            compile(node, program);
+            program.push(Inst::Save(2 * index + 1));
+        }
+        Node::Concat(nodes) => nodes.iter().for_each(|node| compile(node, program)),
+        Node::Alternate(branches) => {
+            let mut jumps = Vec::new();
+            for (i, branch) in branches.iter().enumerate() {
+                if i + 1 < branches.len() {
+                    let split = program.len();
+                    program.push(Inst::Split(split + 1, 0));
+                    compile(branch, program);
+                    jumps.push(program.len());
+                    program.push(Inst::Jump(0));
+                    let next = program.len();
+                    program[split] = Inst::Split(split + 1, next);
+                } else {
+                    compile(branch, program);
+                }
+            }
+            let end = program.len();
+            for jump in jumps {
+                program[jump] = Inst::Jump(end);
+            }
+        }
+        Node::Repeat(node, min, max, greedy) => {
+            for _ in 0..*min {
+                compile(node, program);
+            }
+            match max {
+                None => {
+                    let split = program.len();
+                    program.push(Inst::Split(0, 0));
+                    compile(node, program);
+                    program.push(Inst::Jump(split));
+                    let end = program.len();
+                    program[split] = branch(split + 1, end, *greedy);
+                }
+                Some(max) => {
+                    let mut splits = Vec::new();
+                    for _ in *min..*max {
+                        splits.push(program.len());
+                        program.push(Inst::Split(0, 0));
+                        compile(node, program);
+                    }
+                    let end = program.len();
+                    for split in splits {
+                        program[split] = branch(split + 1, end, *greedy);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn branch(body: usize, skip: usize, greedy: bool) -> Inst {
+    if greedy {
+        Inst::Split(body, skip)
+    } else {
+        Inst::Split(skip, body)
+    }
+}
+
+enum Job {
+    Run(usize, usize),
+    Restore(usize, Option<usize>),
+}
+
+// Searches one input. Split states (instruction, position) that failed to
+// reach a match are remembered across searches, which bounds the work by
+// O(program * input) and stops empty repetitions from looping forever.
+pub struct Matcher<'a> {
+    program: &'a [Inst],
+    input: &'a [char],
+    slot_count: usize,
+    failed: HashSet<usize>,
+    // States entered by the current attempt, which are marked as failed
+    // up front and unmarked again if the attempt matches.
+    visited: Vec<usize>,
+}
+
+impl<'a> Matcher<'a> {
+    // Leftmost match starting at or after char offset `start`.
+    pub fn find_at(&mut self, start: usize) -> Option<Captures> {
+        for position in start..=self.input.len() {
+            let captures = self.run(position);
+            if captures.is_some() {
+                for state in self.visited.drain(..) {
+                    self.failed.remove(&state);
+                }
+                return captures;
+            }
+            self.visited.clear();
+        }
+        None
+    }
+
+    fn run(&mut self, start: usize) -> Option<Captures> {
+        let mut slots = vec![None; self.slot_count];
+        let mut jobs = vec![Job::Run(0, start)];
+        while let Some(job) = jobs.pop() {
+            let (mut pc, mut position) = match job {
+                Job::Run(pc, position) => (pc, position),
+                Job::Restore(slot, value) => {
+                    slots[slot] = value;
+                    continue;
+                }
+            };
+            loop {
+                // Every loop and alternative goes through a split, so it is
+                // enough to remember those states.
+                if let Inst::Split(..) = self.program[pc] {
+                    let state = pc * (self.input.len() + 1) + position;
+                    if !self.failed.insert(state) {
+                        break;
+                    }
+                    self.visited.push(state);
+                }
+                match &self.program[pc] {
+                    Inst::Char(c) => {
+                        if self.input.get(position) != Some(c) {
+                            break;
+                        }
+                        pc += 1;
+                        position += 1;
+                    }
+                    Inst::Any => match self.input.get(position) {
+                        Some('\n') | None => break,
+                        Some(_) => {
+                            pc += 1;
+                            position += 1;
+                        }
+                    },
+                    Inst::Class(items, negated) => match self.input.get(position) {
+                        Some(c) if class_matches(items, *c) != *negated => {
+                            pc += 1;
+                            position += 1;
+                        }
+                        _ => break,
+                    },
+                    Inst::Assert(assertion) => {
+                        if !self.assert(*assertion, position) {
+                            break;
+                        }
+                        pc += 1;
+                    }
+                    Inst::Split(first, second) => {
+                        jobs.push(Job::Run(*second, position));
+                        pc = *first;
+                    }
+                    Inst::Jump(target) => pc = *target,
+                    Inst::Save(slot) => {
+                        jobs.push(Job::Restore(*slot, slots[*slot]));
+                        slots[*slot] = Some(position);
+                        pc += 1;
+                    }
+                    Inst::Match => return Some(slots),
+                }
+            }
+        }
+        None
+    }
+
+    fn assert(&self, assertion: Assertion, position: usize) -> bool {
+        match assertion {
+            Assertion::Start => position == 0,
+            Assertion::End => position == self.input.len(),
+            Assertion::WordBoundary => self.at_boundary(position),
+            Assertion::NotWordBoundary => !self.at_boundary(position),
+        }
+    }
+
+    fn at_boundary(&self, position: usize) -> bool {
+        let before = position > 0 && is_word(self.input[position - 1]);
+        let after = position < self.input.len() && is_word(self.input[position]);
+        before != after
+    }
+}
+
+fn class_matches(items: &[ClassItem], c: char) -> bool {
+    items.iter().any(|item| match item {
+        ClassItem::Range(low, high) => *low <= c && c <= *high,
+        ClassItem::Digit(positive) => c.is_ascii_digit() == *positive,
+        ClassItem::Word(positive) => is_word(c) == *positive,
+        ClassItem::Space(positive) => c.is_whitespace() == *positive,
+    })
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, input: &str, start: usize) -> Option<Vec<Option<usize>>> {
+        let regex = Regex::new(pattern).ok().unwrap();
+        let input: Vec<char> = input.chars().collect();
+        regex.matcher(&input).find_at(start)
+    }
+
+    fn compile_error(pattern: &str) -> (usize, String) {
+        let err = Regex::new(pattern).err().unwrap();
+        (err.position, err.message)
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let deepest = "(".repeat(MAX_DEPTH) + "a" + &")".repeat(MAX_DEPTH);
+        assert_eq!(find(&deepest, "a", 0).map(|slots| slots[1]), Some(Some(1)));
+        let (position, message) = compile_error(&format!("({})", deepest));
+        assert_eq!(position, MAX_DEPTH);
+        assert_eq!(message, "Groups are nested more than 256 deep");
+        let (position, _) = compile_error(&"(?:".repeat(200_000));
+        assert_eq!(position, 3 * MAX_DEPTH);
+    }
+
+    #[test]
+    fn empty_matches() {
+        assert_eq!(find("a*", "bbb", 0), Some(vec![Some(0), Some(0)]));
+        assert_eq!(find("a*", "bbb", 3), Some(vec![Some(3), Some(3)]));
+        assert_eq!(find("a*", "bbb", 4), None);
+        assert_eq!(find("", "", 0), Some(vec![Some(0), Some(0)]));
+        // Empty repetitions must not loop forever.
+        assert_eq!(
+            find("(a|)*b", "aab", 0).map(|slots| slots[1]),
+            Some(Some(3))
+        );
+        assert_eq!(find("(?:)*$", "xy", 0), Some(vec![Some(2), Some(2)]));
+        assert_eq!(find("(a*)*c", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", 0), None);
+    }
+
+    #[test]
+    fn captures() {
+        let slots = find("(\\d+)-(?<b>\\d+)", "x 12-345", 0).unwrap();
+        assert_eq!(
+            slots,
+            vec![Some(2), Some(8), Some(2), Some(4), Some(5), Some(8)]
+        );
+        assert_eq!(
+            Regex::new("(?<b>x)").ok().unwrap().group_index("b"),
+            Some(1)
+        );
+        assert_eq!(find("(a)|b", "b", 0).unwrap()[2], None);
+    }
+
+    #[test]
+    fn backreferences_are_not_supported() {
+        assert_eq!(
+            compile_error("(a)\\1"),
+            (3, "Unknown escape '\\1'".to_string())
+        );
+        assert_eq!(compile_error("(?<x>a)\\k<x>").0, 7);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_eq!(compile_error("(a"), (0, "Unterminated group".to_string()));
+        assert_eq!(compile_error("a)"), (1, "Unmatched ')'".to_string()));
+        assert_eq!(compile_error("a**").1, "Nested quantifier");
+        assert_eq!(
+            compile_error("a{3,2}").1,
+            "Repetition range is out of order"
+        );
+        assert_eq!(compile_error("a{1001}").1, "Repetition count exceeds 1000");
+        assert_eq!(
+            compile_error("[z-a]").1,
+            "Range is out of order in character class"
+        );
+    }
+}
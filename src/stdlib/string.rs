@@ -0,0 +1,238 @@
+use super::{expect_index, expect_str};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::{Arity, Native, NativeFn};
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+// Longer results from repeat() are rejected instead of aborting the
+// process when the allocation fails.
+const MAX_REPEAT_LEN: usize = 1 << 28;
+
+pub fn method(name: &str) -> Option<Native> {
+    let (arity, function): (Arity, NativeFn) = match name {
+        "length" => (Arity::Fixed(0), length),
+        "substring" => (Arity::Fixed(2), substring),
+        "slice" => (Arity::Fixed(2), slice),
+        "indexOf" => (Arity::Fixed(1), index_of),
+        "contains" => (Arity::Fixed(1), contains),
+        "startsWith" => (Arity::Fixed(1), starts_with),
+        "endsWith" => (Arity::Fixed(1), ends_with),
+        "split" => (Arity::Fixed(1), split),
+        "upper" => (Arity::Fixed(0), upper),
+        "lower" => (Arity::Fixed(0), lower),
+        "trim" => (Arity::Fixed(0), trim),
+        "trimStart" => (Arity::Fixed(0), trim_start),
+        "trimEnd" => (Arity::Fixed(0), trim_end),
+        "replace" => (Arity::Fixed(2), replace),
+        "repeat" => (Arity::Fixed(1), repeat),
+        "charAt" => (Arity::Fixed(1), char_at),
+        "codePointAt" => (Arity::Fixed(1), code_point_at),
+        "codePoints" => (Arity::Fixed(0), code_points),
+        "toNumber" => (Arity::Fixed(0), to_number),
+        _ => return None,
+    };
+    Some(Native::new(name, arity, function))
+}
+
+pub fn from_code_point(
+    _: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let mut result = String::new();
+    for arg in args {
+        let code = expect_index(paren, "fromCodePoint", arg)?;
+        match u32::try_from(code).ok().and_then(char::from_u32) {
+            Some(c) => result.push(c),
+            None => {
+                return Err(RuntimeError::new(
+                    ErrorKind::ValueError,
+                    paren.clone(),
+                    &format!("Invalid code point {}.", code),
+                ))
+            }
+        }
+    }
+    Ok(Literal::Str(result))
+}
+
+fn length(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "length", &args[0])?;
+    Ok(Literal::Number(s.chars().count() as f64))
+}
+
+fn substring(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "substring", &args[0])?;
+    let length = s.chars().count() as i64;
+    let start = expect_index(paren, "substring", &args[1])?.clamp(0, length);
+    let end = expect_index(paren, "substring", &args[2])?.clamp(0, length);
+    Ok(Literal::Str(chars_between(s, start, end)))
+}
+
+fn slice(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "slice", &args[0])?;
+    let length = s.chars().count() as i64;
+    let from_end = |index: i64| {
+        if index < 0 {
+            (length + index).max(0)
+        } else {
+            index.min(length)
+        }
+    };
+    let start = from_end(expect_index(paren, "slice", &args[1])?);
+    let end = from_end(expect_index(paren, "slice", &args[2])?);
+    Ok(Literal::Str(chars_between(s, start, end)))
+}
+
+fn index_of(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "indexOf", &args[0])?;
+    let needle = expect_str(paren, "indexOf", &args[1])?;
+    let index = match s.find(needle) {
+        Some(byte) => s[..byte].chars().count() as f64,
+        None => -1.0,
+    };
+    Ok(Literal::Number(index))
+}
+
+fn contains(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "contains", &args[0])?;
+    let needle = expect_str(paren, "contains", &args[1])?;
+    Ok(Literal::Bool(s.contains(needle)))
+}
+
+fn starts_with(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "startsWith", &args[0])?;
+    let prefix = expect_str(paren, "startsWith", &args[1])?;
+    Ok(Literal::Bool(s.starts_with(prefix)))
+}
+
+fn ends_with(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "endsWith", &args[0])?;
+    let suffix = expect_str(paren, "endsWith", &args[1])?;
+    Ok(Literal::Bool(s.ends_with(suffix)))
+}
+
+fn split(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "split", &args[0])?;
+    let separator = expect_str(paren, "split", &args[1])?;
+    let parts: Vec<Literal> = if separator.is_empty() {
+        s.chars().map(|c| Literal::Str(c.to_string())).collect()
+    } else {
+        s.split(separator)
+            .map(|part| Literal::Str(part.to_string()))
+            .collect()
+    };
+    Ok(Literal::List(Rc::new(RefCell::new(parts))))
+}
+
+fn upper(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "upper", &args[0])?;
+    Ok(Literal::Str(s.to_uppercase()))
+}
+
+fn lower(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "lower", &args[0])?;
+    Ok(Literal::Str(s.to_lowercase()))
+}
+
+fn trim(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "trim", &args[0])?;
+    Ok(Literal::Str(s.trim().to_string()))
+}
+
+fn trim_start(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "trimStart", &args[0])?;
+    Ok(Literal::Str(s.trim_start().to_string()))
+}
+
+fn trim_end(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "trimEnd", &args[0])?;
+    Ok(Literal::Str(s.trim_end().to_string()))
+}
+
+fn replace(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "replace", &args[0])?;
+    let from = expect_str(paren, "replace", &args[1])?;
+    let to = expect_str(paren, "replace", &args[2])?;
+    Ok(Literal::Str(s.replace(from, to)))
+}
+
+fn repeat(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "repeat", &args[0])?;
+    let count = expect_index(paren, "repeat", &args[1])?;
+    if count < 0 {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            "Repeat count must not be negative.",
+        ));
+    }
+    let count = count as usize;
+    match s.len().checked_mul(count) {
+        Some(len) if len <= MAX_REPEAT_LEN => Ok(Literal::Str(s.repeat(count))),
+        _ => Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            "Repeated string is too long.",
+        )),
+    }
+}
+
+fn char_at(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "charAt", &args[0])?;
+    let c = nth_char(paren, s, expect_index(paren, "charAt", &args[1])?)?;
+    Ok(Literal::Str(c.to_string()))
+}
+
+fn code_point_at(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "codePointAt", &args[0])?;
+    let c = nth_char(paren, s, expect_index(paren, "codePointAt", &args[1])?)?;
+    Ok(Literal::Number(c as u32 as f64))
+}
+
+fn code_points(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "codePoints", &args[0])?;
+    let points = s
+        .chars()
+        .map(|c| Literal::Number(c as u32 as f64))
+        .collect();
+    Ok(Literal::List(Rc::new(RefCell::new(points))))
+}
+
+fn to_number(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let s = expect_str(paren, "toNumber", &args[0])?;
+    match s.trim().parse::<f64>() {
+        Ok(n) => Ok(Literal::Number(n)),
+        Err(_) => Ok(Literal::Nothing),
+    }
+}
+
+fn chars_between(s: &str, start: i64, end: i64) -> String {
+    if start >= end {
+        return String::new();
+    }
+    s.chars()
+        .skip(start as usize)
+        .take((end - start) as usize)
+        .collect()
+}
+
+fn nth_char(paren: &Token, s: &str, index: i64) -> RuntimeResult<char> {
+    let c = if index < 0 {
+        None
+    } else {
+        s.chars().nth(index as usize)
+    };
+    match c {
+        Some(c) => Ok(c),
+        None => Err(RuntimeError::new(
+            ErrorKind::IndexError,
+            paren.clone(),
+            &format!("String index {} out of range.", index),
+        )),
+    }
+}
@@ -0,0 +1,412 @@
+use super::{define, expect_number, expect_str, module, native};
+use crate::error::report::{ErrorKind, RuntimeError};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::interpreter::{Interpreter, RuntimeResult};
+use crate::interpreter::native::Arity;
+use crate::lexer::literal::Literal;
+use crate::lexer::token::Token;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MS_PER_DAY: i64 = 86_400_000;
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+pub fn load(globals: &mut Environment) {
+    define(globals, "now", Arity::Fixed(0), now);
+    define(globals, "sleep", Arity::Fixed(1), sleep);
+    define(globals, "monotonic", Arity::Fixed(0), monotonic);
+    define(globals, "elapsed", Arity::Fixed(1), elapsed);
+    let date = module(
+        "date",
+        vec![
+            ("utc", native("utc", Arity::Fixed(1), utc)),
+            ("fromUtc", native("fromUtc", Arity::Variadic(3), from_utc)),
+            ("iso", native("iso", Arity::Fixed(1), iso)),
+            ("format", native("format", Arity::Fixed(2), format)),
+        ],
+    );
+    globals.define("date".to_string(), date);
+}
+
+fn now(_: &mut Interpreter, _: &Token, _: &[Literal]) -> RuntimeResult<Literal> {
+    let now = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_millis() as f64,
+        Err(err) => -(err.duration().as_millis() as f64),
+    };
+    Ok(Literal::Number(now))
+}
+
+fn sleep(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let ms = expect_number(paren, "sleep", &args[0])?;
+    if !ms.is_finite() || ms < 0.0 {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            "Argument to 'sleep' must be a non-negative number of milliseconds.",
+        ));
+    }
+    match Duration::try_from_secs_f64(ms / 1000.0) {
+        Ok(duration) => thread::sleep(duration),
+        Err(_) => {
+            return Err(RuntimeError::new(
+                ErrorKind::ValueError,
+                paren.clone(),
+                "Argument to 'sleep' is too long.",
+            ))
+        }
+    }
+    Ok(Literal::Nothing)
+}
+
+// Milliseconds since the interpreter started, unaffected by clock changes.
+fn monotonic(interpreter: &mut Interpreter, _: &Token, _: &[Literal]) -> RuntimeResult<Literal> {
+    let since = interpreter.start_time.elapsed();
+    Ok(Literal::Number(since.as_secs_f64() * 1000.0))
+}
+
+fn elapsed(
+    interpreter: &mut Interpreter,
+    paren: &Token,
+    args: &[Literal],
+) -> RuntimeResult<Literal> {
+    let start = expect_number(paren, "elapsed", &args[0])?;
+    let since = interpreter.start_time.elapsed();
+    Ok(Literal::Number(since.as_secs_f64() * 1000.0 - start))
+}
+
+struct DateTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    second: i64,
+    millisecond: i64,
+    // Monday is 1, Sunday is 7, as in ISO-8601.
+    weekday: i64,
+    year_day: i64,
+}
+
+impl DateTime {
+    fn from_millis(ms: i64) -> DateTime {
+        let days = ms.div_euclid(MS_PER_DAY);
+        let time = ms.rem_euclid(MS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        DateTime {
+            year,
+            month,
+            day,
+            hour: time / 3_600_000,
+            minute: time / 60_000 % 60,
+            second: time / 1000 % 60,
+            millisecond: time % 1000,
+            // 1970-01-01 was a Thursday.
+            weekday: (days + 3).rem_euclid(7) + 1,
+            year_day: days - days_from_civil(year, 1, 1) + 1,
+        }
+    }
+}
+
+// Days since 1970-01-01 to a proleptic Gregorian date, after Howard Hinnant's
+// `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    days_from_civil(year + month / 12, month % 12 + 1, 1) - days_from_civil(year, month, 1)
+}
+
+fn timestamp(paren: &Token, function: &str, value: &Literal) -> RuntimeResult<i64> {
+    let ms = expect_number(paren, function, value)?;
+    // The range of JavaScript dates: 100 million days either side of the
+    // epoch, which is about 275,000 years.
+    if !ms.is_finite() || ms.abs() > 8.64e15 {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            &format!("Timestamp passed to '{}' is out of range.", function),
+        ));
+    }
+    Ok(ms.floor() as i64)
+}
+
+fn utc(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let date = DateTime::from_millis(timestamp(paren, "utc", &args[0])?);
+    let components = vec![
+        ("year", date.year),
+        ("month", date.month),
+        ("day", date.day),
+        ("hour", date.hour),
+        ("minute", date.minute),
+        ("second", date.second),
+        ("millisecond", date.millisecond),
+        ("weekday", date.weekday),
+        ("yearDay", date.year_day),
+    ];
+    Ok(module(
+        "date",
+        components
+            .into_iter()
+            .map(|(name, value)| (name, Literal::Number(value as f64)))
+            .collect(),
+    ))
+}
+
+// fromUtc(year, month, day[, hour, minute, second, millisecond]) -> epoch millis.
+fn from_utc(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    if args.len() > 7 {
+        return Err(RuntimeError::new(
+            ErrorKind::ArityError,
+            paren.clone(),
+            "Wrong number of arguments.",
+        ));
+    }
+    let mut parts = [0i64, 1, 1, 0, 0, 0, 0];
+    for (part, arg) in parts.iter_mut().zip(args) {
+        let n = expect_number(paren, "fromUtc", arg)?;
+        if n.fract() != 0.0 {
+            return Err(RuntimeError::new(
+                ErrorKind::TypeError,
+                paren.clone(),
+                "Arguments to 'fromUtc' must be integers.",
+            ));
+        }
+        // Keeps the arithmetic below far from overflowing.
+        if n.abs() > 1e6 {
+            return Err(RuntimeError::new(
+                ErrorKind::ValueError,
+                paren.clone(),
+                "Arguments to 'fromUtc' must be between -1000000 and 1000000.",
+            ));
+        }
+        *part = n as i64;
+    }
+    let [year, month, day, hour, minute, second, millisecond] = parts;
+    let in_range = (1..=12).contains(&month)
+        && (1..=days_in_month(year, month)).contains(&day)
+        && (0..24).contains(&hour)
+        && (0..60).contains(&minute)
+        && (0..60).contains(&second)
+        && (0..1000).contains(&millisecond);
+    if !in_range {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            "Date component passed to 'fromUtc' is out of range.",
+        ));
+    }
+    let ms = days_from_civil(year, month, day) * MS_PER_DAY
+        + hour * 3_600_000
+        + minute * 60_000
+        + second * 1000
+        + millisecond;
+    Ok(Literal::Number(ms as f64))
+}
+
+fn iso(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let date = DateTime::from_millis(timestamp(paren, "iso", &args[0])?);
+    Ok(Literal::Str(iso_string(&date)))
+}
+
+// Years outside 0000 to 9999 take the expanded ISO-8601 form, with a sign
+// and six digits, as in JavaScript's toISOString().
+fn iso_string(date: &DateTime) -> String {
+    let year = match date.year {
+        0..=9999 => format!("{:04}", date.year),
+        _ if date.year < 0 => format!("-{:06}", -date.year),
+        _ => format!("+{:06}", date.year),
+    };
+    year + &strftime(date, "-%m-%dT%H:%M:%S.%LZ")
+}
+
+fn format(_: &mut Interpreter, paren: &Token, args: &[Literal]) -> RuntimeResult<Literal> {
+    let date = DateTime::from_millis(timestamp(paren, "format", &args[0])?);
+    let pattern = expect_str(paren, "format", &args[1])?;
+    if let Some(bad) = unknown_directive(pattern) {
+        return Err(RuntimeError::new(
+            ErrorKind::ValueError,
+            paren.clone(),
+            &format!("Unknown format directive '{}'.", bad),
+        ));
+    }
+    Ok(Literal::Str(strftime(&date, pattern)))
+}
+
+fn unknown_directive(pattern: &str) -> Option<String> {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '%' {
+            match chars.next() {
+                Some(d) if "YymdejHIMSLpaAbBu%".contains(d) => {}
+                Some(d) => return Some(format!("%{}", d)),
+                None => return Some("%".to_string()),
+            }
+        }
+    }
+    None
+}
+
+fn strftime(date: &DateTime, pattern: &str) -> String {
+    let mut out = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let piece = match chars.next() {
+            Some('Y') => format!("{:04}", date.year),
+            Some('y') => format!("{:02}", date.year.rem_euclid(100)),
+            Some('m') => format!("{:02}", date.month),
+            Some('d') => format!("{:02}", date.day),
+            Some('e') => format!("{:2}", date.day),
+            Some('j') => format!("{:03}", date.year_day),
+            Some('H') => format!("{:02}", date.hour),
+            Some('I') => format!("{:02}", (date.hour + 11) % 12 + 1),
+            Some('M') => format!("{:02}", date.minute),
+            Some('S') => format!("{:02}", date.second),
+            Some('L') => format!("{:03}", date.millisecond),
+            Some('p') => (if date.hour < 12 { "AM" } else { "PM" }).to_string(),
+            Some('a') => WEEKDAYS[date.weekday as usize - 1][..3].to_string(),
+            Some('A') => WEEKDAYS[date.weekday as usize - 1].to_string(),
+            Some('b') => MONTHS[date.month as usize - 1][..3].to_string(),
+            Some('B') => MONTHS[date.month as usize - 1].to_string(),
+            Some('u') => date.weekday.to_string(),
+            Some('%') => "%".to_string(),
+            Some(other) => format!("%{}", other),
+            None => "%".to_string(),
+        };
+        out.push_str(&piece);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::token::{Span, TokenType};
+
+    fn paren() -> Token {
+        Token::new(
+            TokenType::RightParen,
+            ")".to_string(),
+            Span::new(0, 1, 1, 1),
+            0,
+        )
+    }
+
+    fn iso_at(ms: f64) -> String {
+        let ms = timestamp(&paren(), "iso", &Literal::Number(ms)).unwrap();
+        iso_string(&DateTime::from_millis(ms))
+    }
+
+    #[test]
+    fn leap_years() {
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(1900, 2), 28);
+        assert_eq!(days_in_month(2023, 12), 31);
+        assert_eq!(iso_at(951_782_400_000.0), "2000-02-29T00:00:00.000Z");
+        assert_eq!(DateTime::from_millis(1_709_164_800_000).year_day, 60);
+    }
+
+    #[test]
+    fn round_trips_through_days() {
+        for days in [-719_468, -1, 0, 1, 10_957, 2_932_896] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+    }
+
+    #[test]
+    fn negative_timestamps() {
+        assert_eq!(iso_at(-1.0), "1969-12-31T23:59:59.999Z");
+        assert_eq!(iso_at(-0.5), "1969-12-31T23:59:59.999Z");
+        let date = DateTime::from_millis(-86_400_000);
+        assert_eq!((date.year, date.month, date.day), (1969, 12, 31));
+        // 1969-12-31 was a Wednesday.
+        assert_eq!(date.weekday, 3);
+        assert_eq!(iso_at(-62_167_219_200_000.0), "0000-01-01T00:00:00.000Z");
+        assert_eq!(iso_at(-62_167_219_200_001.0), "-000001-12-31T23:59:59.999Z");
+    }
+
+    #[test]
+    fn from_utc_checks_its_arguments() {
+        let mut interpreter = Interpreter::new();
+        let mut call = |args: [f64; 3]| {
+            let args = args.map(Literal::Number);
+            from_utc(&mut interpreter, &paren(), &args)
+        };
+        let leap_day = call([2000.0, 2.0, 29.0]).unwrap();
+        assert_eq!(leap_day, Literal::Number(951_782_400_000.0));
+        let kind = |result: RuntimeResult<Literal>| result.unwrap_err().kind();
+        assert_eq!(kind(call([2000.5, 1.0, 1.0])), ErrorKind::TypeError);
+        assert_eq!(kind(call([1e7, 1.0, 1.0])), ErrorKind::ValueError);
+        assert_eq!(kind(call([2023.0, 2.0, 29.0])), ErrorKind::ValueError);
+    }
+
+    #[test]
+    fn sleep_rejects_unusable_durations() {
+        let mut interpreter = Interpreter::new();
+        for ms in [1e300, f64::MAX, -1.0, f64::NAN, f64::INFINITY] {
+            let args = [Literal::Number(ms)];
+            let err = sleep(&mut interpreter, &paren(), &args).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ValueError);
+        }
+    }
+
+    #[test]
+    fn range_limits() {
+        assert_eq!(iso_at(8.64e15), "+275760-09-13T00:00:00.000Z");
+        assert_eq!(iso_at(-8.64e15), "-271821-04-20T00:00:00.000Z");
+        assert_eq!(iso_at(253_402_300_799_999.0), "9999-12-31T23:59:59.999Z");
+        assert_eq!(iso_at(253_402_300_800_000.0), "+010000-01-01T00:00:00.000Z");
+        for ms in [8.64e15 + 1.0, -8.64e15 - 1.0, f64::NAN, f64::INFINITY] {
+            let err = timestamp(&paren(), "iso", &Literal::Number(ms)).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ValueError);
+        }
+    }
+}